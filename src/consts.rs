@@ -12,8 +12,13 @@ pub use self::objects::*;
 
 use crate::render::text::FontID;
 pub const ASSETS_FOLDER: &str = "assets";
-// text rendering
-pub const DEFAULT_FONT_PATH: &str = "fonts\\InconsolataZi4varlvarquRegular-42gD.ttf";
+// every texture and font (see render::assets), relative to the assets folder
+pub const ASSET_MANIFEST_PATH: &str = "assets.json";
+// every type of tile, relative to the assets folder
+pub const TILES_PATH: &str = "tiles.json";
+// the level pack played when none is given, relative to the assets folder
+pub const DEFAULT_PACK_PATH: &str = "levels/pack.json";
+// text rendering (the default font is the first one in the asset manifest)
 pub const DEFAULT_FONT_ID: FontID = FontID(0);
 // window stuff
 pub const TITLE: &str = "The Horrible Game";
@@ -24,16 +29,43 @@ pub const TILES_U32: u32 = 50;
 // the grid is 20 tiles by 20 tiles
 pub const WINDOW_X: u32 = (GRID_SIZE as u32) * TILES_U32;
 pub const WINDOW_Y: u32 = (GRID_SIZE as u32) * TILES_U32;
-// Map editor dimensions: 52 by 52 board, smaller tiles, and 12 tiles worth of extra room on the right. 
+// Map editor dimensions: 52 by 52 board, smaller tiles, and 20 tiles worth of extra room on the right.
 pub const MEDIT_TILES: u32 = 52;
 pub const MEDIT_TILE_SIZE: f64 = 12.5;
-pub const MEDIT_EXTRA_ROOM: u32 = 12; // 12 tiles worth of extra room
+pub const MEDIT_EXTRA_ROOM: u32 = 20; // 20 tiles worth of extra room: the guide, and the panels beside it
 pub const MEDIT_GUIDE_SIZE: u32 = 3;
-pub const MEDIT_WINDOW_X: u32 = (MEDIT_TILE_SIZE as u32) * (MEDIT_TILES + MEDIT_EXTRA_ROOM);
-pub const MEDIT_WINDOW_Y: u32 = (MEDIT_TILE_SIZE as u32) * MEDIT_TILES;
+// where the minimap goes, to the right of the guide: [x, y, width, height] in tiles
+pub const MEDIT_MINIMAP: [u32; 4] = [
+    MEDIT_TILES + MEDIT_GUIDE_SIZE + 1,
+    1,
+    MEDIT_EXTRA_ROOM - MEDIT_GUIDE_SIZE - 2,
+    MEDIT_EXTRA_ROOM - MEDIT_GUIDE_SIZE - 2,
+];
+// where the status bar goes, below the minimap: [x, y, width, height] in tiles. It has a line per tile.
+pub const MEDIT_HUD: [u32; 4] = [
+    MEDIT_MINIMAP[0],
+    MEDIT_MINIMAP[1] + MEDIT_MINIMAP[3] + 1,
+    MEDIT_MINIMAP[2],
+    MEDIT_TILES - (MEDIT_MINIMAP[1] + MEDIT_MINIMAP[3] + 2),
+];
+pub const MEDIT_HUD_TEXT_SIZE: u32 = 10;
+pub const MEDIT_MESSAGE_TIME: Duration = Duration::from_secs(4); // how long messages stay up for
+pub const MEDIT_AUTOSAVE_TIME: Duration = Duration::from_secs(30); // how often unsaved work is written out
+pub const MEDIT_HISTORY_LIMIT: usize = 200; // how many steps can be undone
+pub const MEDIT_OBJECT_SNAP: f64 = 0.25; // objects are placed, moved and resized in quarter tiles
+pub const MEDIT_ZOOM_STEP: f64 = 1.25; // how much one step of the scroll wheel zooms in by
+pub const MEDIT_MAX_ZOOM: f64 = 8.0;
+pub const MEDIT_WINDOW_X: u32 = (MEDIT_TILE_SIZE * (MEDIT_TILES + MEDIT_EXTRA_ROOM) as f64) as u32;
+pub const MEDIT_WINDOW_Y: u32 = (MEDIT_TILE_SIZE * MEDIT_TILES as f64) as u32;
 // Opengl stuff
 pub const OPENGL: OpenGL = OpenGL::V4_5;
 pub const FRAMERATE: Duration = Duration::from_micros(0); // no maximum framerate
+pub const SNAPSHOT_TOLERANCE: u8 = 2; // how far off a snapshot's pixels can be from the reference image
+
+// the game itself always runs at 60 ticks per second, however fast frames are drawn
+pub const TICK_RATE: u32 = 60;
+pub const TICK_LENGTH: Duration = Duration::from_nanos(1_000_000_000 / TICK_RATE as u64);
+pub const MAX_TICKS_PER_FRAME: u32 = 5; // any further behind than this and the game slows down
 
 // input
 pub const LEFT_MOUSE: u8 = 1;
@@ -41,6 +73,9 @@ pub const RIGHT_MOUSE: u8 = 2;
 pub const MIDDLE_MOUSE: u8 = 4;
 pub const ANY_MOUSE: u8 = 255;
 
+// the camera follows the player once they're this far from the middle of the screen (as a fraction of it)
+pub const CAMERA_DEADZONE: [f64; 2] = [0.15, 0.15];
+
 // the fugde factor. Allows any magnitudes less than this (outside of comparisons to 0) to pass equality and gt/lt checks.
 pub const FUDGE: f64 = 0.01 * GRID_SIZE;
 
@@ -5,8 +5,12 @@ pub const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
 pub const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
 pub const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
 pub const MAGNETA: [f32; 4] = [1.0, 0.0, 1.0, 1.0];
+pub const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+pub const LIGHT_GREY: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+pub const DARK_GREY: [f32; 4] = [0.25, 0.25, 0.25, 1.0];
 
 pub const TRANS_RED: [f32; 4] = [1.0, 0.0, 0.0, 0.5];
 pub const TRANS_GREEN: [f32; 4] = [0.0, 1.0, 0.0, 0.5];
 pub const TRANS_BLUE: [f32; 4] = [0.0, 0.0, 1.0, 0.5];
+pub const FAINT_WHITE: [f32; 4] = [1.0, 1.0, 1.0, 0.25];
 pub const TRANSPARENT: [f32; 4] = [0.0; 4];
@@ -1,28 +1,18 @@
-use graphics::color::{MAGENTA, YELLOW};
-
 use crate::{
     internals::object::{Behavior, BlockTemplate, Environment, ObjectTemplate, Transform},
     render::{
+        animation::{Animation, AnimationMode, Frame},
         composite::Composite,
         rect::Rect,
         text::TextRenderer,
-        texture::{ImageRenderer, TextureID},
+        RenderJob,
     },
 };
 
 use super::{
-    BLUE, CONTENT_LAYER, DEFAULT_FONT_ID, GREEN, GRID_SIZE, MAGNETA, MEDIT_TILE_SIZE, RED, TILES,
-    TRANS_GREEN, TRANS_RED, UI_LAYER, WHITE,
+    DEFAULT_FONT_ID, GREEN, GRID_SIZE, MAGNETA, MEDIT_TILE_SIZE, RED, TILES, UI_LAYER, WHITE,
 };
 
-// textures based on IDs
-pub const SPIKE_TX: TextureID = TextureID(0);
-pub const GOAL_TX: TextureID = TextureID(1);
-pub const WRAP_TX: TextureID = TextureID(2);
-pub const TRANSITION_TX: TextureID = TextureID(3);
-pub const CONVEYOR_L_TX: TextureID = TextureID(4);
-pub const CONVEYOR_R_TX: TextureID = TextureID(5);
-
 pub const GAME_TRANSFORM: Transform = Transform {
     tile_offset: [-1.0; 2],
     tile_size: [GRID_SIZE; 2],
@@ -39,15 +29,15 @@ pub const PLAYER_R_INDICATOR: usize = 2;
 pub const PLAYER_U_INDICATOR: usize = 3;
 pub const PLAYER_D_INDICATOR: usize = 4;
 
-// creates a player object, and returns a template. 
+// creates a player object, and returns a template.
 pub fn player() -> ObjectTemplate {
     let mut player_job = Composite::new([0.0; 4], WHITE);
     let composite = Composite::ensure_mut(&mut player_job);
     composite.add_job(Rect::new(GREEN, [0.0, 0.0, 1.0, 1.0]), true); // the player's body
-    composite.add_job(Rect::new(MAGNETA, [0.1, 0.45, 0.1, 0.1]), false); // leftward movement indicator
-    composite.add_job(Rect::new(MAGNETA, [0.8, 0.45, 0.1, 0.1]), false); // rightward movement indicator
-    composite.add_job(Rect::new(RED, [0.1, 0.05, 0.8, 0.05]), false); // upward movement indicator
-    composite.add_job(Rect::new(RED, [0.1, 0.9, 0.8, 0.05]), false); // downward movement indicator
+    composite.add_job(indicator(MAGNETA, [0.1, 0.45, 0.1, 0.1]), false); // leftward movement indicator
+    composite.add_job(indicator(MAGNETA, [0.8, 0.45, 0.1, 0.1]), false); // rightward movement indicator
+    composite.add_job(indicator(RED, [0.1, 0.05, 0.8, 0.05]), false); // upward movement indicator
+    composite.add_job(indicator(RED, [0.1, 0.9, 0.8, 0.05]), false); // downward movement indicator
     ObjectTemplate {
         x_pos: None,
         y_pos: None,
@@ -59,106 +49,17 @@ pub fn player() -> ObjectTemplate {
         layer: Some(UI_LAYER - 1),
     }
 }
-pub const BLOCK: ObjectTemplate = ObjectTemplate {
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    job: Some(Rect::new(WHITE, [0.0; 4])),
-    layer: Some(CONTENT_LAYER),
-};
-pub const CONVEYOR_L: ObjectTemplate = ObjectTemplate {
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    job: Some(ImageRenderer::new([0.0; 4], WHITE, CONVEYOR_L_TX)),
-    layer: Some(CONTENT_LAYER),
-};
-pub const CONVEYOR_R: ObjectTemplate = ObjectTemplate {
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    job: Some(ImageRenderer::new([0.0; 4], WHITE, CONVEYOR_R_TX)),
-    layer: Some(CONTENT_LAYER),
-};
-pub const STICKY: ObjectTemplate = ObjectTemplate {
-    job: Some(Rect::new(MAGENTA, [0.0; 4])),
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    layer: Some(CONTENT_LAYER),
-};
-pub const SLIME: ObjectTemplate = ObjectTemplate {
-    job: Some(Rect::new(TRANS_GREEN, [0.0; 4])),
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    layer: Some(CONTENT_LAYER),
-};
-pub const WATER: ObjectTemplate = ObjectTemplate {
-    job: Some(Rect::new(BLUE, [0.0; 4])),
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    layer: Some(CONTENT_LAYER),
-};
-pub const FLIPPER: ObjectTemplate = ObjectTemplate {
-    job: Some(Rect::new(TRANS_RED, [0.0; 4])),
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    layer: Some(CONTENT_LAYER),
-};
-pub const SPIKE: ObjectTemplate = ObjectTemplate {
-    job: Some(ImageRenderer::new([0.0; 4], RED, SPIKE_TX)),
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    layer: Some(CONTENT_LAYER),
-};
-pub const GOAL: ObjectTemplate = ObjectTemplate {
-    job: Some(ImageRenderer::new([0.0; 4], YELLOW, GOAL_TX)),
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    layer: Some(CONTENT_LAYER),
-};
-pub const ENEMY: ObjectTemplate = ObjectTemplate {
-    x_pos: None,
-    y_pos: None,
-    x_speed: Some(0.0),
-    y_speed: Some(0.0),
-    width: Some(1.0),
-    height: Some(1.0),
-    job: Some(Rect::new(WHITE, [0.0; 4])),
-    layer: Some(CONTENT_LAYER),
-};
+// a movement indicator, which blinks while it's shown
+fn indicator(color: [f32; 4], bounds: [f64; 4]) -> RenderJob {
+    let frames = [1.0, 0.4]
+        .map(|alpha| Frame {
+            job: Rect::new([color[0], color[1], color[2], alpha], [0.0, 0.0, 1.0, 1.0]).cmp,
+            duration: PLAYER_INDICATOR_BLINK,
+        })
+        .to_vec();
+    Animation::new(frames, AnimationMode::Loop, bounds, WHITE)
+}
+pub const PLAYER_INDICATOR_BLINK: f64 = 0.25; // how long the indicators stay bright (and then dim), in seconds
 pub const PLAYER_ENV: Environment = Environment {
     x_accel: 0.0,
     y_accel: 0.0,
@@ -197,3 +98,6 @@ pub const PLAYER_SPEED_X: f64 = 0.06 * GRID_SIZE;
 pub const PLAYER_SPEED_Y: f64 = 0.12 * GRID_SIZE;
 
 pub const WATER_SPEED_MULTI: f64 = -0.1;
+
+// enemies walk at a constant speed (and fall just as fast as the player does, see Controls::gravity)
+pub const ENEMY_SPEED: f64 = 0.03 * GRID_SIZE;
@@ -1,15 +1,23 @@
+pub mod replay;
+
 use std::collections::HashSet;
 
+use self::replay::{InputEvent, Replay};
+
+#[derive(Clone)]
 pub struct InputVars {
     key_down: HashSet<u32>,
     key_changed: HashSet<u32>,
     mouse_pressed: u8,
     mouse_changed: u8,
     pub mouse_pos: [f64; 2],
+    pub scroll: [f64; 2], // how far the mouse wheel moved ([x, y]) since the last reset
     pub has_focus: bool,
     pub has_cursor: bool,
     pub dimensions: [f64; 2],
     pub text_buffer: String,
+    pub frame: u64,                // how many times the input has been reset so far
+    pub recording: Option<Replay>, // if set, every change gets written down here
 }
 impl InputVars {
     pub fn new() -> InputVars {
@@ -19,18 +27,51 @@ impl InputVars {
             mouse_pressed: 0,
             mouse_changed: 0,
             mouse_pos: [0.0, 0.0],
+            scroll: [0.0, 0.0],
             has_focus: true,
             has_cursor: true,
             dimensions: [0.0, 0.0],
             text_buffer: String::new(),
+            frame: 0,
+            recording: None,
         }
     }
-    // should happen every frame
+    // should happen every time the input has been consumed (once per game tick or editor frame)
     pub fn reset(&mut self) {
         self.key_changed.clear();
         self.mouse_changed = 0;
+        self.scroll = [0.0, 0.0];
+        self.frame += 1;
+    }
+    pub fn record(&mut self) {
+        self.recording = Some(Replay::new());
+    }
+    // stops recording, handing back everything recorded so far.
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        let mut res = self.recording.take()?;
+        res.frames = self.frame;
+        Some(res)
+    }
+    fn log(&mut self, event: InputEvent) {
+        if let Some(recording) = &mut self.recording {
+            recording.events.push((self.frame, event));
+        }
+    }
+    // applies a recorded change as if it came from the window.
+    pub fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key(code, state) => self.update_key(code, state),
+            InputEvent::Mouse(code, state) => self.update_mouse(code, state),
+            InputEvent::MousePos(pos) => self.update_mouse_pos(pos),
+            InputEvent::Scroll(delta) => self.update_scroll(delta),
+            InputEvent::Text(text) => self.push_text(&text),
+            InputEvent::Focus(val) => self.focus(val),
+            InputEvent::Cursor(val) => self.cursor(val),
+            InputEvent::Resize(val) => self.update_dimension(val),
+        }
     }
     pub fn update_key(&mut self, code: u32, state: bool) {
+        self.log(InputEvent::Key(code, state));
         self.key_changed.insert(code);
         if state {
             self.key_down.insert(code);
@@ -48,6 +89,7 @@ impl InputVars {
         !self.key_down.contains(&code) && self.key_changed.contains(&code)
     }
     pub fn update_mouse(&mut self, code: u8, state: bool) {
+        self.log(InputEvent::Mouse(code, state));
         self.mouse_changed |= code;
         if state {
             self.mouse_pressed |= code;
@@ -64,13 +106,29 @@ impl InputVars {
     pub fn mouse_released(&self, code: u8) -> bool {
         ((self.mouse_pressed & code) == 0) && ((self.mouse_changed & code) != 0)
     }
+    pub fn update_mouse_pos(&mut self, pos: [f64; 2]) {
+        self.log(InputEvent::MousePos(pos));
+        self.mouse_pos = pos;
+    }
+    pub fn update_scroll(&mut self, delta: [f64; 2]) {
+        self.log(InputEvent::Scroll(delta));
+        self.scroll[0] += delta[0];
+        self.scroll[1] += delta[1];
+    }
+    pub fn push_text(&mut self, text: &str) {
+        self.log(InputEvent::Text(text.to_string()));
+        self.text_buffer.push_str(text);
+    }
     pub fn focus(&mut self, input: bool) {
+        self.log(InputEvent::Focus(input));
         self.has_focus = input;
     }
     pub fn cursor(&mut self, input: bool) {
+        self.log(InputEvent::Cursor(input));
         self.has_cursor = input;
     }
     pub fn update_dimension(&mut self, input: [f64; 2]) {
+        self.log(InputEvent::Resize(input));
         self.dimensions = input;
     }
     pub fn grab_text(&mut self) -> String {
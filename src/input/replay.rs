@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::InputVars;
+
+// a single change to the input, as fed in by Window::handle_input
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    Key(u32, bool),
+    Mouse(u8, bool),
+    MousePos([f64; 2]),
+    Scroll([f64; 2]),
+    Text(String),
+    Focus(bool),
+    Cursor(bool),
+    Resize([f64; 2]),
+}
+// every input change of a play session, each tagged with the frame (game tick) it happened before.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub events: Vec<(u64, InputEvent)>,
+    pub frames: u64, // how many frames the session lasted
+}
+impl Replay {
+    pub fn new() -> Replay {
+        Replay::default()
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, String> {
+        serde_json::from_slice(&std::fs::read(path).map_err(|x| x.to_string())?)
+            .map_err(|x| x.to_string())
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let data = serde_json::to_string(self).map_err(|x| x.to_string())?;
+        std::fs::write(path, data).map_err(|x| x.to_string())
+    }
+    pub fn player(&self) -> ReplayPlayer<'_> {
+        ReplayPlayer::new(self)
+    }
+}
+// feeds a replay back into a fresh InputVars, one frame at a time.
+pub struct ReplayPlayer<'a> {
+    replay: &'a Replay,
+    next_event: usize,
+    started: bool,
+    pub input: InputVars,
+}
+impl<'a> ReplayPlayer<'a> {
+    pub fn new(replay: &'a Replay) -> ReplayPlayer<'a> {
+        ReplayPlayer {
+            replay,
+            next_event: 0,
+            started: false,
+            input: InputVars::new(),
+        }
+    }
+    // moves on to the next frame. Returns None once the replay is over.
+    pub fn next_frame(&mut self) -> Option<&InputVars> {
+        if self.started {
+            self.input.reset();
+        }
+        self.started = true;
+        if self.input.frame >= self.replay.frames {
+            return None;
+        }
+        self.apply_events();
+        Some(&self.input)
+    }
+    fn apply_events(&mut self) {
+        while let Some((frame, event)) = self.replay.events.get(self.next_event) {
+            if *frame > self.input.frame {
+                break;
+            }
+            self.input.apply(event.clone());
+            self.next_event += 1;
+        }
+    }
+}
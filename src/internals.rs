@@ -1,172 +1,209 @@
 pub mod controls;
+pub mod enemy;
 pub mod levels;
 pub mod object;
 pub mod partition_map;
+pub mod platform;
+pub mod tiles;
+pub mod timestep;
 
 use std::mem::take;
 
 use crate::{
     consts::{
-        player, BLOCK, CONVEYOR_L, CONVEYOR_R, FLIPPER, FUDGE, GAME_TRANSFORM, GOAL, GRID_SIZE,
-        NUM_TIMES, PLAYER_ENV, SLIME, SPIKE, STICKY, WATER, WINDOW_X, WINDOW_Y,
+        player, FUDGE, GAME_TRANSFORM, GRID_SIZE, NUM_TIMES, PLAYER_ENV, TILES, WINDOW_X, WINDOW_Y,
     },
-    input::InputVars,
-    render::RenderJobs,
+    input::{replay::Replay, InputVars},
+    render::{RenderJobID, RenderJobs},
 };
 
 use self::{
     controls::Controls,
-    levels::{GridSpace, Levels},
-    object::{Behavior, Block, CollideAction, Direction, Environment, Object},
+    enemy::Enemy,
+    levels::Levels,
+    object::{Block, CollideAction, Direction, Environment, Object, Transform},
     partition_map::{Partition, PartitionMap},
+    platform::Platform,
+    tiles::TileKind,
 };
 
 pub struct Game {
     // the contents of a level
     pub player: Option<Object>,
     pub partitioner: PartitionMap,
-    // TODO: Any interactables that move must recalculate their partition every frame.
-    // This doesn't need to be done immediately due to the lack of interactables.
+    // Any interactables that move must recalculate their partition every frame (see Enemy::tick).
     // Additionally: deleting interactables can be done by setting the entry to None.
     pub interactables: Vec<Option<Block>>,
     pub non_interactables: Vec<Block>,
+    pub enemies: Vec<Enemy>,
+    pub platforms: Vec<Platform>,
     pub player_env: Environment,
     pub current_level: usize,
     pub current_pos: [usize; 2],
     pub levels: Levels,
     pub controls: Controls,
+    // render jobs of objects that were dropped since the last render, waiting to be removed
+    pub dropped_jobs: Vec<RenderJobID>,
     // etc
 }
+// what happened during a single call to Game::tick
+#[derive(Clone, Debug)]
+pub struct StepReport {
+    pub player_pos: Option<[f64; 2]>,
+    pub actions: Vec<CollideAction>, // every action that fired, in order. CollideAction::None is left out.
+    pub level: usize,
+    pub screen: [usize; 2],
+}
 impl Game {
-    pub fn new(jobs: &mut RenderJobs) -> Game {
+    // Creates a game. Nothing is rendered until Game::render is called, so this works without a window.
+    pub fn new(levels: Levels) -> Game {
+        let current_level = levels.first();
         let mut game = Game {
             player: None,
-            partitioner: PartitionMap::new(Partition { x: 0, y: 0 }),
+            partitioner: PartitionMap::new(Partition {
+                x: 0,
+                y: 0,
+                screens: [0; 2],
+            }),
             interactables: Vec::new(),
             non_interactables: Vec::new(),
+            enemies: Vec::new(),
+            platforms: Vec::new(),
             player_env: PLAYER_ENV,
-            current_level: 1,
-            levels: Levels::new(),
+            current_level,
+            levels,
             controls: Controls::new(),
             current_pos: [0, 0],
+            dropped_jobs: Vec::new(),
         };
-        game.new_level(jobs);
+        game.new_level();
         game
     }
-    pub fn new_level(&mut self, jobs: &mut RenderJobs) {
-        self.drop_table_level(jobs);
-        let level = &self.levels.levels[self.current_level];
-        self.current_pos = [level.player_start[0], level.player_start[1]];
-        let grid = &level.grid[level.player_start[1]][level.player_start[0]];
-        for (i, line) in grid.contents.iter().enumerate() {
-            for (j, block) in line.iter().enumerate() {
-                let (template, behavior) = match block {
-                    GridSpace::Block => (BLOCK, Behavior::Stop),
-                    GridSpace::Spike => (SPIKE, Behavior::Kill),
-                    GridSpace::Enemy => todo!(),
-                    GridSpace::Goal => (GOAL, Behavior::Advance),
-                    GridSpace::StartingLocation => {
-                        self.player = Some(
-                            player()
-                                .x_pos(j as f64)
-                                .y_pos(i as f64)
-                                .to_object(jobs, &GAME_TRANSFORM)
-                                .unwrap(),
-                        );
-                        continue;
-                    }
-                    GridSpace::None => continue,
-                    GridSpace::Transition => (BLOCK, Behavior::Portal),
-                    GridSpace::Wrap => (BLOCK, Behavior::Wrap),
-                    GridSpace::StickyBlock => (STICKY, Behavior::Stick),
-                    GridSpace::ConveyorR => (CONVEYOR_R, Behavior::Move(Direction::Right)),
-                    GridSpace::ConveyorL => (CONVEYOR_L, Behavior::Move(Direction::Left)),
-                    GridSpace::Slime => (SLIME, Behavior::Slime),
-                    GridSpace::Water => (WATER, Behavior::Water),
-                    GridSpace::Flipper => (FLIPPER, Behavior::Flip),
-                };
-                let object = template
-                    .x_pos(j as f64)
-                    .y_pos(i as f64)
-                    .to_object(jobs, &GAME_TRANSFORM)
-                    .unwrap();
-                let mut block = Block::new(object, behavior);
-                block.object.tick(&Vec::new(), jobs);
-                self.partitioner.add(block.object.partition);
-                self.interactables.push(Some(block));
-            }
-        }
-        for line in &grid.others {
-            let block = line.clone().to_block(jobs, &GAME_TRANSFORM).unwrap();
-            self.partitioner.add(block.object.partition);
-            self.interactables.push(Some(block));
-        }
+    pub fn new_level(&mut self) {
+        self.drop_table_level();
+        self.current_pos = self.levels.levels[self.current_level].player_start;
+        self.load_screens(true);
     }
-    pub fn dead(&mut self, jobs: &mut RenderJobs) {
-        self.current_level = 0;
-        self.new_level(jobs);
+    pub fn dead(&mut self) {
+        self.current_level = self.levels.hub;
+        self.new_level();
     }
-    pub fn next_level(&mut self, jobs: &mut RenderJobs) {
-        self.current_level += 1;
-        self.new_level(jobs);
+    pub fn next_level(&mut self) {
+        self.current_level = self.levels.next(self.current_level);
+        self.new_level();
     }
     // does a reset
-    pub fn drop_table_level(&mut self, jobs: &mut RenderJobs) {
-        for line in take(&mut self.player) {
-            line.drop(jobs);
-        }
-        for line in take(&mut self.interactables) {
-            line.map(|x| x.object.drop(jobs));
+    pub fn drop_table_level(&mut self) {
+        if let Some(player) = take(&mut self.player) {
+            self.dropped_jobs.extend(player.job_id);
         }
+        self.drop_table();
         self.controls = Controls::new();
-        self.partitioner.clear();
     }
-    pub fn drop_table(&mut self, jobs: &mut RenderJobs) {
-        for line in take(&mut self.interactables) {
-            line.map(|x| x.object.drop(jobs));
+    pub fn drop_table(&mut self) {
+        for line in take(&mut self.interactables).into_iter().flatten() {
+            self.dropped_jobs.extend(line.object.job_id);
         }
         for line in take(&mut self.non_interactables) {
-            line.object.drop(jobs);
+            self.dropped_jobs.extend(line.object.job_id);
         }
+        self.enemies.clear();
+        self.platforms.clear();
         self.partitioner.clear();
     }
-    pub fn load_grid(&mut self, jobs: &mut RenderJobs) {
-        self.drop_table(jobs);
-        for (i, line) in self.levels.levels[self.current_level].grid[self.current_pos[0]]
-            [self.current_pos[1]]
-            .contents
-            .iter()
-            .enumerate()
-        {
+    pub fn load_grid(&mut self) {
+        self.drop_table();
+        self.load_screens(false);
+    }
+    // Adds everything on the current screen, or on every screen of a scrolling level. The player is
+    // only placed when starting the level.
+    fn load_screens(&mut self, spawn_player: bool) {
+        let level = &self.levels.levels[self.current_level];
+        let screens: Vec<[usize; 2]> = match level.scrolling {
+            true => (0..level.grid.len())
+                .flat_map(|row| (0..level.grid[row].len()).map(move |column| [row, column]))
+                .collect(),
+            false => vec![self.current_pos],
+        };
+        for screen in screens {
+            let transform = self.screen_transform(screen);
+            self.load_tiles(screen, &transform, spawn_player);
+            self.load_others(screen, &transform);
+            self.load_platforms(screen, &transform);
+        }
+    }
+    // where a screen ([row, column]) goes in the world. Screens of a scrolling level are laid out
+    // next to each other; otherwise they're all in the same place.
+    fn screen_transform(&self, screen: [usize; 2]) -> Transform {
+        let offset = match self.levels.levels[self.current_level].scrolling {
+            true => [(screen[1] * TILES) as f64, (screen[0] * TILES) as f64],
+            false => [0.0; 2],
+        };
+        Transform {
+            tile_offset: [
+                GAME_TRANSFORM.tile_offset[0] + offset[0],
+                GAME_TRANSFORM.tile_offset[1] + offset[1],
+            ],
+            tile_size: GAME_TRANSFORM.tile_size,
+        }
+    }
+    // how big the world is, in pixels: one screen, or all of them in a scrolling level
+    pub fn world_size(&self) -> [f64; 2] {
+        let level = &self.levels.levels[self.current_level];
+        match level.scrolling {
+            true => [
+                level.grid[0].len() as f64 * WINDOW_X as f64,
+                level.grid.len() as f64 * WINDOW_Y as f64,
+            ],
+            false => [WINDOW_X as f64, WINDOW_Y as f64],
+        }
+    }
+    fn load_tiles(&mut self, screen: [usize; 2], transform: &Transform, spawn_player: bool) {
+        let level = &self.levels.levels[self.current_level];
+        let count = [level.grid.len(), level.grid[0].len()];
+        // Neighbouring screens of a scrolling level overlap by a tile on each side, so the border
+        // tiles are left out wherever there's another screen.
+        let hidden = |tile: usize, axis: usize| {
+            level.scrolling
+                && ((tile == 0 && screen[axis] > 0)
+                    || (tile == TILES + 1 && screen[axis] + 1 < count[axis]))
+        };
+        for (i, line) in level.grid[screen[0]][screen[1]].contents.iter().enumerate() {
             for (j, block) in line.iter().enumerate() {
-                let (template, behavior) = match block {
-                    GridSpace::Block => (BLOCK, Behavior::Stop),
-                    GridSpace::Spike => (SPIKE, Behavior::Kill),
-                    GridSpace::Enemy => todo!(),
-                    GridSpace::Goal => (GOAL, Behavior::Advance),
-                    GridSpace::StartingLocation | GridSpace::None => continue,
-                    GridSpace::Transition => (BLOCK, Behavior::Portal),
-                    GridSpace::Wrap => (BLOCK, Behavior::Wrap),
-                    GridSpace::StickyBlock => (STICKY, Behavior::Stick),
-                    GridSpace::ConveyorR => (CONVEYOR_R, Behavior::Move(Direction::Right)),
-                    GridSpace::ConveyorL => (CONVEYOR_L, Behavior::Move(Direction::Left)),
-                    GridSpace::Slime => (SLIME, Behavior::Slime),
-                    GridSpace::Water => (WATER, Behavior::Water),
-                    GridSpace::Flipper => (FLIPPER, Behavior::Flip),
-                };
-                let object = template
-                    .x_pos(j as f64)
-                    .y_pos(i as f64)
-                    .to_object(jobs, &GAME_TRANSFORM)
-                    .unwrap();
-                let mut block = Block::new(object, behavior);
-                block.object.tick(&Vec::new(), jobs);
-                self.partitioner.add(block.object.partition);
-                self.interactables.push(Some(block));
+                if hidden(i, 0) || hidden(j, 1) {
+                    continue;
+                }
+                let def = block.def();
+                let template = def.template().x_pos(j as f64).y_pos(i as f64);
+                match def.kind {
+                    TileKind::Empty => continue,
+                    TileKind::Player => {
+                        if spawn_player {
+                            self.player = Some(
+                                player()
+                                    .x_pos(j as f64)
+                                    .y_pos(i as f64)
+                                    .to_object(transform)
+                                    .unwrap(),
+                            );
+                        }
+                        continue;
+                    }
+                    TileKind::Block | TileKind::Enemy => {}
+                }
+                let object = template.to_object(transform).unwrap();
+                let id = self.partitioner.add(object.partition);
+                self.interactables
+                    .push(Some(Block::new(object, def.behavior)));
+                if def.kind == TileKind::Enemy {
+                    self.enemies.push(Enemy::new(id));
+                }
             }
         }
-        for line in &self.levels.levels[self.current_level].start().others {
-            let block = line.clone().to_block(jobs, &GAME_TRANSFORM).unwrap();
+    }
+    fn load_others(&mut self, screen: [usize; 2], transform: &Transform) {
+        for line in &self.levels.levels[self.current_level].grid[screen[0]][screen[1]].others {
+            let block = line.to_block(transform).unwrap();
             if block.interactable() {
                 self.partitioner.add(block.object.partition);
                 self.interactables.push(Some(block));
@@ -175,16 +212,48 @@ impl Game {
             }
         }
     }
-    pub fn tick(&mut self, jobs: &mut RenderJobs, input: &mut InputVars) {
+    fn load_platforms(&mut self, screen: [usize; 2], transform: &Transform) {
+        for template in &self.levels.levels[self.current_level].grid[screen[0]][screen[1]].platforms
+        {
+            if let Some((block, mut platform)) = template.to_platform(transform) {
+                platform.id = self.partitioner.add(block.object.partition);
+                self.interactables.push(Some(block));
+                self.platforms.push(platform);
+            }
+        }
+    }
+    // advances the game by one frame. Doesn't touch any render jobs; see Game::render.
+    pub fn tick(&mut self, input: &InputVars) -> StepReport {
+        let mut fired = Vec::new();
+        if let Some(player) = &mut self.player {
+            player.save_pos();
+        }
+        let moving = self.enemies.iter().map(|x| x.id);
+        for id in moving.chain(self.platforms.iter().map(|x| x.id)) {
+            if let Some(block) = &mut self.interactables[id.0] {
+                block.object.save_pos();
+            }
+        }
         // processes player collisions with blocks
         for _ in 0..NUM_TIMES {
+            for platform in &mut self.platforms {
+                platform.tick(
+                    &mut self.interactables,
+                    &mut self.partitioner,
+                    self.player.as_mut(),
+                );
+            }
+            for enemy in &mut self.enemies {
+                enemy.tick(
+                    &mut self.interactables,
+                    &mut self.partitioner,
+                    &self.player_env,
+                );
+            }
             let player = self.player.as_mut().unwrap();
-            player.tick(&vec![&self.player_env], jobs);
+            player.tick(&[&self.player_env]);
             self.partitioner.set_player(player.partition());
-            let player_job = jobs
-                .get_job_mut(player.job_id)
-                .expect("The player isn't rendered!");
-            self.controls.update_player(player, player_job, input);
+            self.controls.update_player(player, input);
             let mut action_queue = Vec::new();
             // let mut counter = 0; // DEBUG
             for line in &self.partitioner.cache {
@@ -221,14 +290,12 @@ impl Game {
                     }
                 }
             }
-            action_queue.sort_by(|x, y| x.1.cmp(&y.1));
+            action_queue.sort_by_key(|x| x.1);
             let action_queue = action_queue.into_iter().map(|x| {
-                self.interactables[x.0].as_mut().unwrap().on_touch(
-                    player,
-                    x.2,
-                    &mut self.controls,
-                    jobs,
-                )
+                self.interactables[x.0]
+                    .as_mut()
+                    .unwrap()
+                    .on_touch(player, x.2, &mut self.controls)
             });
             // println!("NUMBER OF BLOCKS GONE THROUGH: {}", counter);
             // println!("TOTAL BLOCKS: {}", self.interactables.len());
@@ -238,6 +305,9 @@ impl Game {
             let mut will_move_screen: Option<Direction> = None;
             let mut will_wrap: Option<Direction> = None;
             for line in action_queue {
+                if line != CollideAction::None {
+                    fired.push(line);
+                }
                 match line {
                     CollideAction::Advance => will_advance = true,
                     CollideAction::Kill => will_die = true,
@@ -246,13 +316,15 @@ impl Game {
                     CollideAction::None => (),
                 }
             }
-            {
-                let _x = player;
-            };
+            // a scrolling level is all one screen, so moving screens goes around it like a wrap does
+            if self.levels.levels[self.current_level].scrolling {
+                will_wrap = will_wrap.or(will_move_screen.take());
+            }
+            let [width, height] = self.world_size();
             if will_die {
-                self.dead(jobs);
+                self.dead();
             } else if will_advance {
-                self.next_level(jobs);
+                self.next_level();
             } else if let Some(dir) = will_move_screen {
                 match dir {
                     Direction::Up => {
@@ -270,7 +342,7 @@ impl Game {
                         {
                             self.current_pos[0] = 0;
                         }
-                        self.player.as_mut().unwrap().y_pos = (WINDOW_Y as f64) - GRID_SIZE - FUDGE;
+                        self.player.as_mut().unwrap().y_pos = height - GRID_SIZE - FUDGE;
                     }
                     Direction::Left => {
                         if self.current_pos[1] == 0 {
@@ -288,26 +360,299 @@ impl Game {
                         {
                             self.current_pos[1] = 0;
                         }
-                        self.player.as_mut().unwrap().x_pos = (WINDOW_X as f64) - GRID_SIZE - FUDGE;
+                        self.player.as_mut().unwrap().x_pos = width - GRID_SIZE - FUDGE;
                     }
                 }
-                self.load_grid(jobs);
+                self.player.as_mut().unwrap().save_pos();
+                self.load_grid();
             } else if let Some(dir) = will_wrap {
                 match dir {
                     Direction::Up => {
                         self.player.as_mut().unwrap().y_pos = FUDGE;
                     }
                     Direction::Down => {
-                        self.player.as_mut().unwrap().y_pos = (WINDOW_Y as f64) - GRID_SIZE - FUDGE;
+                        self.player.as_mut().unwrap().y_pos = height - GRID_SIZE - FUDGE;
                     }
                     Direction::Left => {
                         self.player.as_mut().unwrap().x_pos = FUDGE;
                     }
                     Direction::Right => {
-                        self.player.as_mut().unwrap().x_pos = (WINDOW_X as f64) - GRID_SIZE - FUDGE;
+                        self.player.as_mut().unwrap().x_pos = width - GRID_SIZE - FUDGE;
                     }
                 }
+                self.player.as_mut().unwrap().save_pos();
+            }
+        }
+        if self.levels.levels[self.current_level].scrolling {
+            // the screen the player's on, for reporting
+            if let Some([x, y]) = self.player_pos() {
+                let level = &self.levels.levels[self.current_level];
+                self.current_pos = [
+                    ((y / WINDOW_Y as f64).max(0.0) as usize).min(level.grid.len() - 1),
+                    ((x / WINDOW_X as f64).max(0.0) as usize).min(level.grid[0].len() - 1),
+                ];
+            }
+        }
+        StepReport {
+            player_pos: self.player_pos(),
+            actions: fired,
+            level: self.current_level,
+            screen: self.current_pos,
+        }
+    }
+    // runs one tick per input state and reports what happened on each of them.
+    pub fn simulate<'a>(
+        &mut self,
+        inputs: impl IntoIterator<Item = &'a InputVars>,
+    ) -> Vec<StepReport> {
+        inputs.into_iter().map(|input| self.tick(input)).collect()
+    }
+    // plays a replay from start to end, reporting what happened on each frame.
+    pub fn simulate_replay(&mut self, replay: &Replay) -> Vec<StepReport> {
+        let mut player = replay.player();
+        let inputs: Vec<InputVars> = std::iter::from_fn(|| player.next_frame().cloned()).collect();
+        self.simulate(&inputs)
+    }
+    pub fn player_pos(&self) -> Option<[f64; 2]> {
+        self.player.as_ref().map(|x| [x.x_pos, x.y_pos])
+    }
+    // Brings the render jobs up to date with the game. Only needed when there's something to draw on.
+    // alpha is how far along the next tick is (see Timestep::alpha), and is used to smooth out movement.
+    pub fn render(&mut self, jobs: &mut RenderJobs, alpha: f64) {
+        // decorations with one-shot animations (effects) are done with once they've played through
+        let finished = jobs.finished_animations();
+        self.non_interactables.retain(|block| {
+            let done = block.object.job_id.is_some_and(|x| finished.contains(&x));
+            if done {
+                self.dropped_jobs.extend(block.object.job_id);
             }
+            !done
+        });
+        for id in take(&mut self.dropped_jobs) {
+            jobs.remove_job(id);
+        }
+        for block in self.interactables.iter_mut().flatten() {
+            block.object.render(jobs, alpha);
+        }
+        for block in &mut self.non_interactables {
+            block.object.render(jobs, alpha);
+        }
+        let [width, height] = self.world_size();
+        jobs.camera.bounds = Some([0.0, 0.0, width, height]);
+        if let Some(player) = &mut self.player {
+            let player_job = player.render(jobs, alpha);
+            self.controls.render(player_job);
+            let target = *player_job.bounds();
+            jobs.camera.follow(target);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use piston::Key;
+
+    use super::{
+        levels::{Level, LevelGrid},
+        object::BlockTemplate,
+        platform::PlatformTemplate,
+        tiles::tiles,
+        *,
+    };
+
+    fn game() -> Game {
+        Game::new(Levels::from_level(Level::load("assets/levels/l1").unwrap()))
+    }
+    // A level with one screen that's empty except for some rows of tiles (see the symbols in
+    // assets/tiles.json) and platforms. The first row is the 20th, and the first column the 10th.
+    fn sketch(rows: &[&str], platforms: Vec<PlatformTemplate>) -> Game {
+        let mut screen = LevelGrid::new();
+        screen.platforms = platforms;
+        for (i, row) in rows.iter().enumerate() {
+            for (j, symbol) in row.chars().enumerate() {
+                screen.contents[20 + i][10 + j] = tiles().with_symbol(symbol).unwrap();
+            }
+        }
+        Game::new(Levels::from_level(Level {
+            name: String::from("sketch"),
+            grid: vec![vec![screen]],
+            player_start: [0, 0],
+            scrolling: false,
+        }))
+    }
+    // the first enemy, and which way it's going
+    fn enemy(game: &Game) -> (&Object, f64) {
+        let enemy = &game.enemies[0];
+        let block = game.interactables[enemy.id.0].as_ref().unwrap();
+        (&block.object, enemy.direction)
+    }
+    // whether two positions are within a tenth of a tile of each other
+    fn near(a: [f64; 2], b: [f64; 2]) -> bool {
+        a.iter()
+            .zip(b)
+            .all(|(a, b)| (a - b).abs() < GRID_SIZE / 10.0)
+    }
+    // ticks until the first enemy turns around, and returns where it was then
+    fn until_turned(game: &mut Game) -> [f64; 2] {
+        let (_, direction) = enemy(game);
+        for _ in 0..1000 {
+            game.tick(&InputVars::new());
+            let (object, now) = enemy(game);
+            if now != direction {
+                return [object.x_pos, object.y_pos];
+            }
+        }
+        panic!("the enemy never turned around");
+    }
+    #[test]
+    fn replays_match_input_sequences() {
+        // right on frame 10, then left on frame 40
+        let mut inputs = vec![InputVars::new(); 90];
+        inputs[10].update_key(Key::Right as u32, true);
+        inputs[40].update_key(Key::Left as u32, true);
+        let mut recorded = InputVars::new();
+        recorded.record();
+        for i in 0..90 {
+            match i {
+                10 => recorded.update_key(Key::Right as u32, true),
+                11 => recorded.update_key(Key::Right as u32, false),
+                40 => recorded.update_key(Key::Left as u32, true),
+                _ => {}
+            }
+            recorded.reset();
+        }
+        let replay = recorded.stop_recording().unwrap();
+        assert_eq!(replay.frames, 90);
+        let positions = |reports: Vec<StepReport>| -> Vec<_> {
+            reports.into_iter().map(|x| x.player_pos).collect()
+        };
+        let expected = positions(game().simulate(&inputs));
+        assert_eq!(expected, positions(game().simulate_replay(&replay)));
+        // the player did actually move
+        assert_ne!(expected[0], expected[89]);
+    }
+    #[test]
+    fn enemies_turn_around_at_walls() {
+        let mut game = sketch(
+            &[
+                "P_________",
+                "BBB_______",
+                "B_____B___",
+                "B_E___B___",
+                "BBBBBBBBBB",
+            ],
+            Vec::new(),
+        );
+        // it walks right until the wall, then back left until the other wall
+        let (object, _) = enemy(&game);
+        let (start, width) = ([object.x_pos, object.y_pos], object.width);
+        let right = until_turned(&mut game);
+        assert!(near(right, [start[0] + 4.0 * GRID_SIZE - width, start[1]]));
+        let left = until_turned(&mut game);
+        assert!(near(left, [start[0] - GRID_SIZE, start[1]]));
+    }
+    #[test]
+    fn enemies_turn_around_at_ledges() {
+        let mut game = sketch(
+            &[
+                "P_________",
+                "BBB_______",
+                "__________",
+                "____E_____",
+                "__BBBBBB__",
+            ],
+            Vec::new(),
+        );
+        // it turns right at the edge, so it never falls off
+        let (object, _) = enemy(&game);
+        let (start, width) = ([object.x_pos, object.y_pos], object.width);
+        let right = until_turned(&mut game);
+        assert!(near(right, [start[0] + 4.0 * GRID_SIZE - width, start[1]]));
+        let left = until_turned(&mut game);
+        assert!(near(left, [start[0] - 2.0 * GRID_SIZE, start[1]]));
+    }
+    #[test]
+    fn enemies_kill_the_player() {
+        let mut game = sketch(&["B__E___P__B", "BBBBBBBBBBB"], Vec::new());
+        let killed = (0..1000).any(|_| {
+            let report = game.tick(&InputVars::new());
+            report.actions.contains(&CollideAction::Kill)
+        });
+        assert!(killed);
+    }
+    // a block three tiles wide that goes along a path
+    fn platform(path: Vec<[f64; 2]>, looping: bool) -> PlatformTemplate {
+        let block = tiles().named("Block").unwrap().def();
+        PlatformTemplate {
+            block: BlockTemplate {
+                object: block.template().width(3.0),
+                behavior: block.behavior,
+            },
+            path,
+            speed: 0.1,
+            looping,
+        }
+    }
+    // ticks until the first platform has headed for every point in order, checking that it gets
+    // to each one on the way
+    fn follows(game: &mut Game, order: &[usize]) {
+        let mut order = order.iter();
+        let mut target = game.platforms[0].target;
+        assert_eq!(Some(&target), order.next());
+        for _ in 0..1000 {
+            let Some(next) = order.clone().next() else {
+                return;
+            };
+            game.tick(&InputVars::new());
+            let platform = &game.platforms[0];
+            if platform.target != target {
+                let object = &game.interactables[platform.id.0].as_ref().unwrap().object;
+                assert!(near([object.x_pos, object.y_pos], platform.path[target]));
+                assert_eq!(platform.target, *next);
+                target = platform.target;
+                order.next();
+            }
+        }
+        panic!("the platform never got through its path");
+    }
+    #[test]
+    fn platforms_turn_around_at_the_ends() {
+        let path = vec![[12.0, 26.0], [16.0, 26.0], [16.0, 23.0]];
+        let mut game = sketch(&["P", "B"], vec![platform(path, false)]);
+        follows(&mut game, &[0, 1, 2, 1, 0, 1, 2]);
+    }
+    #[test]
+    fn looping_platforms_go_back_to_the_start() {
+        let path = vec![[12.0, 26.0], [16.0, 26.0], [16.0, 23.0]];
+        let mut game = sketch(&["P", "B"], vec![platform(path, true)]);
+        follows(&mut game, &[0, 1, 2, 0, 1, 2, 0]);
+    }
+    // where the player and the first platform are
+    fn player_and_platform(game: &Game) -> ([f64; 2], [f64; 2]) {
+        let player = game.player.as_ref().unwrap();
+        let block = game.interactables[game.platforms[0].id.0].as_ref().unwrap();
+        (
+            [player.x_pos, player.y_pos],
+            [block.object.x_pos, block.object.y_pos],
+        )
+    }
+    #[test]
+    fn platforms_carry_the_player() {
+        let path = vec![[10.0, 22.0], [30.0, 22.0]];
+        let mut game = sketch(&["_", "P"], vec![platform(path, false)]);
+        // lets the player land first
+        for _ in 0..30 {
+            game.tick(&InputVars::new());
+        }
+        let (player, platform) = player_and_platform(&game);
+        for _ in 0..60 {
+            game.tick(&InputVars::new());
         }
+        // it moved six tiles, and the player moved right along with it
+        let (moved, moved_platform) = player_and_platform(&game);
+        assert!(near(
+            moved_platform,
+            [platform[0] + 6.0 * GRID_SIZE, platform[1]]
+        ));
+        assert!(near(moved, [player[0] + 6.0 * GRID_SIZE, player[1]]));
     }
 }
@@ -20,74 +20,55 @@ pub struct Controls {
     pub can_be_flipped_y: u8, // 10 -> 1: can't flip, 0: can flip.
 }
 impl Controls {
-    pub fn new_level(&mut self, _: &mut Object, renderer: &mut RenderJob, _: &mut InputVars) {
-        let composite = Composite::ensure_mut(renderer);
-        composite.toggle_job(PLAYER_L_INDICATOR, false);
-        composite.toggle_job(PLAYER_R_INDICATOR, false);
-        composite.toggle_job(PLAYER_U_INDICATOR, false);
-        composite.toggle_job(PLAYER_D_INDICATOR, false);
+    pub fn new_level(&mut self) {
         self.horizontal_direction = 0.0;
         self.vertical_direction = 0.0;
     }
-    pub fn left(&mut self, player: &mut Object, renderer: &mut RenderJob) {
-        let composite = Composite::ensure_mut(renderer);
-        composite.toggle_job(PLAYER_L_INDICATOR, true);
-        composite.toggle_job(PLAYER_R_INDICATOR, false);
+    pub fn left(&mut self) {
         self.horizontal_direction = -1.0;
     }
-    pub fn right(&mut self, player: &mut Object, renderer: &mut RenderJob) {
-        let composite = Composite::ensure_mut(renderer);
-        composite.toggle_job(PLAYER_L_INDICATOR, false);
-        composite.toggle_job(PLAYER_R_INDICATOR, true);
+    pub fn right(&mut self) {
         self.horizontal_direction = 1.0;
     }
-    pub fn up(&mut self, player: &mut Object, renderer: &mut RenderJob) {
-        let composite = Composite::ensure_mut(renderer);
-        composite.toggle_job(PLAYER_U_INDICATOR, true);
-        composite.toggle_job(PLAYER_D_INDICATOR, false);
+    pub fn up(&mut self) {
         self.vertical_direction = -1.0;
     }
-    pub fn up_internal(&mut self, player: &mut Object, rendereer: &mut RenderJob) {
+    pub fn up_internal(&mut self) {
         self.gravity_y = -1.0;
     }
-    pub fn down_internal(&mut self, player: &mut Object, renderer: &mut RenderJob) {
+    pub fn down_internal(&mut self) {
         self.gravity_y = 1.0;
     }
-    pub fn down(&mut self, player: &mut Object, renderer: &mut RenderJob) {
-        let composite = Composite::ensure_mut(renderer);
-        composite.toggle_job(PLAYER_U_INDICATOR, false);
-        composite.toggle_job(PLAYER_D_INDICATOR, true);
+    pub fn down(&mut self) {
         self.vertical_direction = 1.0;
     }
-    pub fn space(&mut self, player: &mut Object, renderer: &mut RenderJob) {
-        let composite = Composite::ensure_mut(renderer);
-        composite.toggle_job(PLAYER_L_INDICATOR, false);
-        composite.toggle_job(PLAYER_R_INDICATOR, false);
-        composite.toggle_job(PLAYER_U_INDICATOR, false);
-        composite.toggle_job(PLAYER_D_INDICATOR, false);
+    pub fn space(&mut self) {
         self.horizontal_direction = 0.0;
         self.vertical_direction = 0.0;
     }
-    pub fn update_player(
-        &mut self,
-        player: &mut Object,
-        renderer: &mut RenderJob,
-        input: &mut InputVars,
-    ) {
+    // shows the movement indicators that match the current directions on the player's job.
+    pub fn render(&self, renderer: &mut RenderJob) {
+        let composite = Composite::ensure_mut(renderer);
+        composite.toggle_job(PLAYER_L_INDICATOR, self.horizontal_direction < 0.0);
+        composite.toggle_job(PLAYER_R_INDICATOR, self.horizontal_direction > 0.0);
+        composite.toggle_job(PLAYER_U_INDICATOR, self.vertical_direction < 0.0);
+        composite.toggle_job(PLAYER_D_INDICATOR, self.vertical_direction > 0.0);
+    }
+    pub fn update_player(&mut self, player: &mut Object, input: &InputVars) {
         if input.key_pressed(Key::Left as u32) || input.key_pressed(Key::A as u32) {
-            self.left(player, renderer);
+            self.left();
         }
         if input.key_pressed(Key::Right as u32) || input.key_pressed(Key::D as u32) {
-            self.right(player, renderer);
+            self.right();
         }
         if input.key_pressed(Key::Up as u32) || input.key_pressed(Key::W as u32) {
-            self.up(player, renderer);
+            self.up();
         }
         if input.key_pressed(Key::Down as u32) || input.key_pressed(Key::S as u32) {
-            self.down(player, renderer);
+            self.down();
         }
         if input.key_pressed(Key::Space as u32) || input.key_pressed(Key::Z as u32) {
-            self.space(player, renderer);
+            self.space();
         }
         if self.can_flip_x {
             self.gravity_x = self.horizontal_direction;
@@ -96,13 +77,17 @@ impl Controls {
         if self.can_flip_y && self.vertical_direction != 0.0 {
             self.gravity_y = self.vertical_direction;
         }
-        player.y_speed += self.gravity_y * PLAYER_SPEED_Y / NUM_TIMES_F64;
+        player.y_speed += self.gravity_y * Controls::gravity();
         self.can_flip_y = false;
         self.can_flip_x = true;
         if self.can_be_flipped_y > 0 {
             self.can_be_flipped_y -= 1;
         }
     }
+    // how much gravity speeds things up by every collision check. Enemies fall with it too.
+    pub fn gravity() -> f64 {
+        PLAYER_SPEED_Y / NUM_TIMES_F64
+    }
     pub fn new() -> Controls {
         Controls {
             horizontal_direction: 0.0,
@@ -0,0 +1,97 @@
+use crate::consts::{ENEMY_SPEED, FUDGE};
+
+use super::{
+    controls::Controls,
+    object::{bounds_partition, Block, Environment, Object},
+    partition_map::{PartitionMap, PartitionMapID},
+};
+
+// An enemy walks back and forth, turning around at walls and ledges. The enemy itself is a
+// killing block stored in Game::interactables; this just drives it around.
+pub struct Enemy {
+    pub id: PartitionMapID, // which of the interactables is this enemy
+    pub direction: f64,     // left (-1) or right (1)
+}
+impl Enemy {
+    pub fn new(id: PartitionMapID) -> Enemy {
+        Enemy { id, direction: 1.0 }
+    }
+    // Moves the enemy by one collision check's worth, and keeps its partition up to date.
+    pub fn tick(
+        &mut self,
+        interactables: &mut [Option<Block>],
+        partitioner: &mut PartitionMap,
+        env: &Environment,
+    ) {
+        // takes the enemy out so it can be checked against everything else
+        let mut enemy = match interactables[self.id.0].take() {
+            Some(val) => val,
+            None => return,
+        };
+        let object = &mut enemy.object;
+        object.x_speed = self.direction * ENEMY_SPEED;
+        object.tick(&[env]);
+        // just like the player, gravity is applied after moving so that standing on something can cancel it out
+        object.y_speed += Controls::gravity();
+        object.partition = object.partition();
+        let mut grounded = false;
+        let mut blocked = false;
+        for block in interactables.iter_mut().flatten() {
+            if !block.solid() || !block.object.partition.collides(&object.partition) {
+                continue;
+            }
+            let [up, down, left, right] = block.collides(object);
+            if up {
+                if object.y_speed > 0.0 {
+                    object.y_speed = 0.0;
+                }
+                grounded = true;
+            }
+            if down && object.y_speed < 0.0 {
+                object.y_speed = 0.0;
+            }
+            if (left && self.direction > 0.0) || (right && self.direction < 0.0) {
+                blocked = true;
+            }
+        }
+        if blocked || (grounded && self.at_ledge(object, interactables, partitioner)) {
+            self.direction = -self.direction;
+        }
+        partitioner.set(self.id, object.partition);
+        interactables[self.id.0] = Some(enemy);
+    }
+    // whether there's nothing to stand on right in front of the enemy
+    fn at_ledge(
+        &self,
+        object: &Object,
+        interactables: &[Option<Block>],
+        partitioner: &PartitionMap,
+    ) -> bool {
+        let x = if self.direction > 0.0 {
+            object.x_pos + object.width + FUDGE
+        } else {
+            object.x_pos - FUDGE
+        };
+        let y = object.y_pos + object.height + FUDGE;
+        let probe = [x - FUDGE, y - FUDGE, x + FUDGE, y + FUDGE];
+        // only what shares a partition with the probe could be under it
+        let partition = bounds_partition([probe[0], probe[1], FUDGE * 2.0, FUDGE * 2.0]);
+        let mut nearby = partitioner
+            .map
+            .iter()
+            .filter(|(_, x)| x.collides(&partition))
+            .filter_map(|(id, _)| interactables[id.0].as_ref());
+        !nearby.any(|block| {
+            block.solid()
+                && Object::touching(
+                    probe,
+                    [
+                        block.object.x_pos,
+                        block.object.y_pos,
+                        block.object.x_pos + block.object.width,
+                        block.object.y_pos + block.object.height,
+                    ],
+                )
+        })
+    }
+}
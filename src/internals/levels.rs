@@ -1,49 +1,109 @@
-use graphics::color::{MAGENTA, YELLOW};
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 use serde_json::from_slice;
 
-use crate::{
-    consts::{
-        self, objects::SPIKE_TX, BLUE, CONVEYOR_L_TX, CONVEYOR_R_TX, GOAL_TX, GREEN, RED,
-        TRANSITION_TX, TRANSPARENT, TRANS_GREEN, TRANS_RED, WHITE, WRAP_TX,
-    },
-    medit::IOMap,
-    render::{rect::Rect, texture::ImageRenderer, RenderJob},
+use crate::{consts, medit::IOMap, render::RenderJob};
+
+use super::{
+    object::BlockTemplate,
+    platform::PlatformTemplate,
+    tiles::{tiles, TileDef},
 };
 
-use super::object::BlockTemplate;
+// A level pack's manifest: every level in the order they're played, plus the death/hub room.
+#[derive(Serialize, Deserialize)]
+pub struct PackManifest {
+    pub levels: Vec<PackEntry>,
+}
+#[derive(Serialize, Deserialize)]
+pub struct PackEntry {
+    pub name: String,  // the display name
+    pub path: PathBuf, // relative to the manifest
+    #[serde(default)]
+    pub hub: bool, // where the player goes after dying or finishing the last level. Exactly one level is the hub.
+    #[serde(default)]
+    pub scrolling: bool, // see Level::scrolling. Levels that scroll by themselves do whatever this says.
+    // There's no way out of the level, like a "you win" screen. It doesn't need a goal to pass
+    // validation.
+    #[serde(default)]
+    pub ending: bool,
+}
+impl PackManifest {
+    pub fn load(path: impl AsRef<Path>) -> Result<PackManifest, String> {
+        let data = std::fs::read(path).map_err(|x| x.to_string())?;
+        from_slice(&data).map_err(|x| x.to_string())
+    }
+}
 
 pub struct Levels {
+    pub hub: usize, // the death/hub room
     pub levels: Vec<Level>,
     // any unique extra bits that a level has (such as tutorial text)
 }
 impl Levels {
-    pub fn new() -> Levels {
-        let paths = vec![
-            "assets/levels/death",
-            "assets/levels/l1",
-            "assets/levels/l2",
-            "assets/levels/l3",
-        ];
+    // loads every level in a level pack.
+    pub fn load(manifest_path: impl AsRef<Path>) -> Result<Levels, String> {
+        let manifest_path = manifest_path.as_ref();
+        let manifest = PackManifest::load(manifest_path)?;
+        let folder = manifest_path.parent().unwrap_or(Path::new(""));
+        let mut hub = None;
+        let mut levels = Vec::new();
+        for (i, entry) in manifest.levels.into_iter().enumerate() {
+            if entry.hub && hub.replace(i).is_some() {
+                return Err("A level pack can only have one hub level!".to_string());
+            }
+            let mut level = Level::load(folder.join(&entry.path))
+                .map_err(|x| format!("{}: {}", entry.path.display(), x))?;
+            level.name = entry.name;
+            level.scrolling |= entry.scrolling;
+            levels.push(level);
+        }
+        let hub = hub.ok_or("A level pack needs a hub level!")?;
+        Ok(Levels { levels, hub })
+    }
+    // a set of levels that only contains one level.
+    pub fn from_level(level: Level) -> Levels {
         Levels {
-            levels: paths
-                .into_iter()
-                .map(|x| {
-                    from_slice::<IOMap>(&std::fs::read(x).expect("Error reading level!"))
-                        .expect("Error parsing level!")
-                        .into_level()
-                })
-                .collect(),
+            levels: vec![level],
+            hub: 0,
+        }
+    }
+    // the level after this one, skipping the hub. After the last level, it's back to the hub.
+    pub fn next(&self, current: usize) -> usize {
+        ((current + 1)..self.levels.len())
+            .find(|x| *x != self.hub)
+            .unwrap_or(self.hub)
+    }
+    // the first level that's played
+    pub fn first(&self) -> usize {
+        if self.hub == 0 {
+            self.next(0)
+        } else {
+            0
         }
     }
 }
 pub struct Level {
+    pub name: String,
     pub grid: Vec<Vec<LevelGrid>>,
-    pub player_start: [usize; 2],
+    pub player_start: [usize; 2], // [row, column] of the screen the player starts on
+    // If set, the screens are put together into one big area that the camera scrolls around,
+    // instead of being flipped between.
+    pub scrolling: bool,
 }
 impl Level {
+    // reads a level from a file (in either format). It's named after the file until something says otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Level, String> {
+        let path = path.as_ref();
+        let mut level = IOMap::load(path)?.0.into_level();
+        if let Some(name) = path.file_name() {
+            level.name = name.to_string_lossy().into_owned();
+        }
+        Ok(level)
+    }
     pub fn start(&self) -> &LevelGrid {
-        &self.grid[self.player_start[1]][self.player_start[0]]
+        &self.grid[self.player_start[0]][self.player_start[1]]
     }
 }
 
@@ -51,38 +111,14 @@ impl Level {
 pub struct LevelGrid {
     pub contents: Vec<Vec<GridSpace>>,
     pub others: Vec<BlockTemplate>,
+    pub platforms: Vec<PlatformTemplate>,
 }
 impl LevelGrid {
     pub fn new() -> LevelGrid {
         LevelGrid {
-            contents: vec![vec![GridSpace::None; consts::TILES + 2]; consts::TILES + 2],
-            others: Vec::new(),
-        }
-    }
-    // DEPRECATED
-    pub fn from_str(contents: String) -> LevelGrid {
-        let mut lines = 0;
-        let mut res = vec![vec![]];
-        for c in contents.chars() {
-            match c {
-                'B' => res[lines].push(GridSpace::Block),
-                'S' => res[lines].push(GridSpace::Spike),
-                'E' => res[lines].push(GridSpace::Enemy),
-                'P' => res[lines].push(GridSpace::StartingLocation),
-                'G' => res[lines].push(GridSpace::Goal),
-                'W' => res[lines].push(GridSpace::Wrap),
-                'T' => res[lines].push(GridSpace::Transition),
-                '_' => res[lines].push(GridSpace::None),
-                '\n' => {
-                    res.push(Vec::new());
-                    lines += 1;
-                }
-                _ => {}
-            }
-        }
-        LevelGrid {
-            contents: res,
+            contents: vec![vec![GridSpace::empty(); consts::TILES + 2]; consts::TILES + 2],
             others: Vec::new(),
+            platforms: Vec::new(),
         }
     }
     pub fn add_others(mut self, others: Vec<BlockTemplate>) -> Self {
@@ -91,54 +127,20 @@ impl LevelGrid {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub enum GridSpace {
-    // stops the player from moving
-    Block,
-    // kills the player
-    Spike,
-    // kills the player, moves
-    Enemy,
-    // advances the player one level
-    Goal,
-    // a player starts here
-    StartingLocation,
-    // if the player goes here and is on the edge of the board, they move to another level.
-    Transition,
-    // if the player goes here and is on the edge of the board, they move to the other side of THIS level.
-    Wrap,
-    // This block is sticky. The player cannot move side to side while touching it downwards, and vice versa.
-    StickyBlock,
-    // this block moves the player while it's on it.
-    ConveyorR,
-    ConveyorL,
-    Slime,
-    Water,
-    // Flips the player
-    Flipper,
-    // there is nothing here
-    None,
-}
+// A tile in a level: an index into the tile registry (see tiles()).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GridSpace(pub usize);
 impl GridSpace {
-    pub const MAX: usize = 14;
-    pub fn from_id(id: usize) -> GridSpace {
-        match id {
-            0 => GridSpace::None,
-            1 => GridSpace::Block,
-            2 => GridSpace::Spike,
-            3 => GridSpace::Enemy,
-            4 => GridSpace::Goal,
-            5 => GridSpace::StartingLocation,
-            6 => GridSpace::Transition,
-            7 => GridSpace::Wrap,
-            8 => GridSpace::StickyBlock,
-            9 => GridSpace::ConveyorR,
-            10 => GridSpace::ConveyorL,
-            11 => GridSpace::Slime,
-            12 => GridSpace::Water,
-            13 => GridSpace::Flipper,
-            _ => GridSpace::None,
-        }
+    // there is nothing here
+    pub fn empty() -> GridSpace {
+        tiles().empty()
+    }
+    // a player starts here
+    pub fn start() -> GridSpace {
+        tiles().start()
+    }
+    pub fn def(self) -> &'static TileDef {
+        tiles().get(self)
     }
     pub fn location(x: u32, y: u32, grid_size: f64, offset: [f64; 2]) -> [f64; 4] {
         [
@@ -148,52 +150,22 @@ impl GridSpace {
             grid_size,
         ]
     }
-    pub fn job_generator(&self, bounds: [f64; 4], color: [f32; 4]) -> RenderJob {
-        match self {
-            GridSpace::Spike => ImageRenderer::new(bounds, color, SPIKE_TX),
-            GridSpace::Goal => ImageRenderer::new(bounds, color, GOAL_TX),
-            GridSpace::Transition => ImageRenderer::new(bounds, color, TRANSITION_TX),
-            GridSpace::ConveyorR => ImageRenderer::new(bounds, color, CONVEYOR_R_TX),
-            GridSpace::ConveyorL => ImageRenderer::new(bounds, color, CONVEYOR_L_TX),
-            GridSpace::Block
-            | GridSpace::Enemy
-            | GridSpace::StartingLocation
-            | GridSpace::Wrap
-            | GridSpace::StickyBlock
-            | GridSpace::None
-            | GridSpace::Slime
-            | GridSpace::Water
-            | GridSpace::Flipper => Rect::new(color, bounds),
-        }
+    pub fn job_generator(self, bounds: [f64; 4], color: [f32; 4]) -> RenderJob {
+        self.def().job(bounds, color)
     }
-    pub fn to_render_job(&self) -> RenderJob {
+    pub fn to_render_job(self) -> RenderJob {
         self.job_generator([0.0; 4], self.color())
     }
-    pub fn alter_render_job(&self, job: &mut RenderJob) {
+    pub fn alter_render_job(self, job: &mut RenderJob) {
         *job = self.job_generator(*job.bounds(), self.color());
     }
-    pub fn alter_render_job_mouse(&self, job: &mut RenderJob) {
+    pub fn alter_render_job_mouse(self, job: &mut RenderJob) {
         let bounds = *job.bounds();
         let mut color = self.color();
         color[3] = 0.5; // half opaque for mouse hovering
         *job = self.job_generator(bounds, color);
     }
-    pub fn color(&self) -> [f32; 4] {
-        match self {
-            GridSpace::Block => WHITE,
-            GridSpace::Spike => RED,
-            GridSpace::Enemy => RED,
-            GridSpace::Goal => YELLOW,
-            GridSpace::StartingLocation => GREEN,
-            GridSpace::Transition => YELLOW,
-            GridSpace::Wrap => YELLOW,
-            GridSpace::StickyBlock => MAGENTA,
-            GridSpace::ConveyorR => YELLOW,
-            GridSpace::ConveyorL => YELLOW,
-            GridSpace::Slime => TRANS_GREEN,
-            GridSpace::Water => BLUE,
-            GridSpace::None => TRANSPARENT,
-            GridSpace::Flipper => TRANS_RED,
-        }
+    pub fn color(self) -> [f32; 4] {
+        self.def().color
     }
 }
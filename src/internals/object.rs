@@ -15,16 +15,19 @@ pub struct Object {
     pub y_pos: f64,
     pub x_speed: f64,
     pub y_speed: f64,
-    pub x_speed_multi: f64, // one-frame multiplication of x speed
-    pub y_speed_multi: f64, // one-frame multiplication of y speed`
-    pub job_id: RenderJobID,
+    pub x_speed_multi: f64,          // one-frame multiplication of x speed
+    pub y_speed_multi: f64,          // one-frame multiplication of y speed`
+    pub job: RenderJob,              // what the object looks like once it gets rendered
+    pub layer: u64,                  // the layer it gets rendered on
+    pub job_id: Option<RenderJobID>, // None until the object has been rendered for the first time
+    pub last_pos: [f64; 2],          // where the object was at the start of the current tick
     pub width: f64,
     pub height: f64,
     pub partition: Partition,
 }
 impl Object {
     pub fn touching(b1: [f64; 4], b2: [f64; 4]) -> bool {
-        return (b1[0] < b2[2]) && (b1[2] > b2[0]) && (b1[1] < b2[3]) && (b1[3] > b2[1]);
+        (b1[0] < b2[2]) && (b1[2] > b2[0]) && (b1[1] < b2[3]) && (b1[3] > b2[1])
     }
     pub fn collides_bounds(&self, other_bounds: [f64; 4]) -> [bool; 4] {
         let this_bounds = [
@@ -76,7 +79,7 @@ impl Object {
         // the top of the other object
         self.collides_bounds(other_bounds)
     }
-    pub fn tick(&mut self, envs: &Vec<&Environment>, jobs: &mut RenderJobs) {
+    pub fn tick(&mut self, envs: &[&Environment]) {
         self.x_pos += self.x_speed * self.x_speed_multi / NUM_TIMES_F64;
         self.y_pos += self.y_speed * self.y_speed_multi / NUM_TIMES_F64;
         for env in envs {
@@ -107,45 +110,78 @@ impl Object {
             self.x_speed += env.x_accel / NUM_TIMES_F64;
             self.y_speed += env.y_accel / NUM_TIMES_F64;
         }
-        let extracted_job = jobs.get_job_mut(self.job_id).expect("safe unwrap");
-        let bounds = extracted_job.bounds();
-        bounds[0] = self.x_pos;
-        bounds[1] = self.y_pos;
-        bounds[2] = self.width;
-        bounds[3] = self.height;
         self.x_speed_multi = 1.0;
         self.y_speed_multi = 1.0;
     }
-    pub fn drop(self, jobs: &mut RenderJobs) {
-        jobs.remove_job(self.job_id);
+    // remembers the current position, so rendering can interpolate from it
+    pub fn save_pos(&mut self) {
+        self.last_pos = [self.x_pos, self.y_pos];
     }
-    pub fn partition(&self) -> Partition {
-        let mut x = 0;
-        let mut y = 0;
-        for i in 0..NUM_PARTITIONS {
-            let min_x = i as f64
-                * (((WINDOW_X as f64) + GRID_SIZE * 2.0) / (NUM_PARTITIONS as f64))
-                - GRID_SIZE;
-            let max_x = (i + 1) as f64
-                * (((WINDOW_X as f64) + GRID_SIZE * 2.0) / (NUM_PARTITIONS as f64))
-                - GRID_SIZE;
-            if self.x_pos < max_x && (self.x_pos + self.width) > min_x {
-                x += 1;
-            }
-            let min_y = i as f64
-                * (((WINDOW_Y as f64) + GRID_SIZE * 2.0) / (NUM_PARTITIONS as f64))
-                - GRID_SIZE;
-            let max_y = (i + 1) as f64
-                * (((WINDOW_Y as f64) + GRID_SIZE * 2.0) / (NUM_PARTITIONS as f64))
-                - GRID_SIZE;
-            if self.y_pos < max_y && (self.y_pos + self.height) > min_y {
-                y += 1;
+    // Pushes the object's position into its render job, creating the job the first time around.
+    // alpha is how far along the next tick is: 0 draws the last position, 1 draws the current one.
+    pub fn render<'a>(&mut self, jobs: &'a mut RenderJobs, alpha: f64) -> &'a mut RenderJob {
+        let id = match self.job_id {
+            Some(id) => id,
+            None => {
+                let id = jobs.add_job(self.job.clone(), self.layer);
+                self.job_id = Some(id);
+                id
             }
-            x <<= 1;
-            y <<= 1;
+        };
+        let extracted_job = jobs.get_job_mut(id).expect("safe unwrap");
+        let bounds = extracted_job.bounds();
+        bounds[0] = self.last_pos[0] + (self.x_pos - self.last_pos[0]) * alpha;
+        bounds[1] = self.last_pos[1] + (self.y_pos - self.last_pos[1]) * alpha;
+        bounds[2] = self.width;
+        bounds[3] = self.height;
+        extracted_job
+    }
+    pub fn partition(&self) -> Partition {
+        bounds_partition([self.x_pos, self.y_pos, self.width, self.height])
+    }
+}
+// which partitions an area ([x, y, width, height]) is in
+pub fn bounds_partition(bounds: [f64; 4]) -> Partition {
+    let [x, y, width, height] = bounds;
+    Partition {
+        x: partition_bits(x, width, WINDOW_X as f64),
+        y: partition_bits(y, height, WINDOW_Y as f64),
+        screens: [
+            screen_bits(x, width, WINDOW_X as f64),
+            screen_bits(y, height, WINDOW_Y as f64),
+        ],
+    }
+}
+// Which partitions a span ([start, start + size]) along one axis is in, as bits. The partitions
+// cover a screen (plus a tile on each side) and repeat every screen, so that levels bigger than a
+// screen still work: something a screen away shares partitions with what's here.
+fn partition_bits(start: f64, size: f64, screen: f64) -> u64 {
+    if size >= screen {
+        return span_bits(-GRID_SIZE, screen + GRID_SIZE * 2.0, screen);
+    }
+    let start = start.rem_euclid(screen);
+    [start - screen, start, start + screen]
+        .into_iter()
+        .fold(0, |res, x| res | span_bits(x, size, screen))
+}
+// which screens a span is on (or within a tile of), as bits that repeat every 64 screens
+fn screen_bits(start: f64, size: f64, screen: f64) -> u64 {
+    let first = ((start - GRID_SIZE) / screen).floor() as i64;
+    let last = ((start + size + GRID_SIZE) / screen).floor() as i64;
+    (first..=last.min(first + 63)).fold(0, |res, x| res | 1 << x.rem_euclid(64))
+}
+fn span_bits(start: f64, size: f64, screen: f64) -> u64 {
+    let mut res = 0;
+    for i in 0..NUM_PARTITIONS {
+        let min = i as f64 * ((screen + GRID_SIZE * 2.0) / (NUM_PARTITIONS as f64)) - GRID_SIZE;
+        let max =
+            (i + 1) as f64 * ((screen + GRID_SIZE * 2.0) / (NUM_PARTITIONS as f64)) - GRID_SIZE;
+        if start < max && (start + size) > min {
+            res += 1;
         }
-        Partition { x, y }
+        res <<= 1;
     }
+    res
 }
 
 pub struct Environment {
@@ -166,7 +202,7 @@ pub struct Transform {
     pub tile_size: [f64; 2],
 }
 #[derive(Clone, Serialize, Deserialize)]
-
+#[serde(default)] // anything left out is filled in from ObjectTemplate::new
 pub struct ObjectTemplate {
     pub x_pos: Option<f64>, // xpos in tiles
     pub y_pos: Option<f64>, // ypos in tiles
@@ -177,6 +213,11 @@ pub struct ObjectTemplate {
     pub job: Option<RenderJob>,
     pub layer: Option<u64>,
 }
+impl Default for ObjectTemplate {
+    fn default() -> Self {
+        ObjectTemplate::new()
+    }
+}
 impl ObjectTemplate {
     pub fn new() -> ObjectTemplate {
         ObjectTemplate {
@@ -222,26 +263,28 @@ impl ObjectTemplate {
         self.layer = Some(new);
         self
     }
-    pub fn to_object(&self, jobs: &mut RenderJobs, transform: &Transform) -> Option<Object> {
-        let mut other_job = self.job.clone()?;
-        let bounds = other_job.bounds();
-        bounds[0] = (self.x_pos? as f64 + transform.tile_offset[0]) * transform.tile_size[0];
-        bounds[1] = (self.y_pos? as f64 + transform.tile_offset[1]) * transform.tile_size[1];
-        bounds[2] = self.width? * transform.tile_size[0];
-        bounds[3] = self.height? * transform.tile_size[1];
-        let id = jobs.add_job(self.job.clone()?, self.layer?);
+    // builds the object without rendering it. Call Object::render to give it a render job.
+    pub fn to_object(&self, transform: &Transform) -> Option<Object> {
         let mut res = Object {
-            x_pos: bounds[0],
-            y_pos: bounds[1],
+            x_pos: (self.x_pos? + transform.tile_offset[0]) * transform.tile_size[0],
+            y_pos: (self.y_pos? + transform.tile_offset[1]) * transform.tile_size[1],
             x_speed: self.x_speed?,
             y_speed: self.y_speed?,
-            job_id: id,
-            width: bounds[2],
-            height: bounds[3],
-            partition: Partition { x: 0, y: 0 },
+            job: self.job.clone()?,
+            layer: self.layer?,
+            job_id: None,
+            width: self.width? * transform.tile_size[0],
+            height: self.height? * transform.tile_size[1],
+            partition: Partition {
+                x: 0,
+                y: 0,
+                screens: [0; 2],
+            },
             x_speed_multi: 1.0,
             y_speed_multi: 1.0,
+            last_pos: [0.0; 2],
         };
+        res.save_pos();
         res.partition = res.partition();
         Some(res)
     }
@@ -268,18 +311,17 @@ impl BlockTemplate {
             behavior: Behavior::None,
         }
     }
-    pub fn to_block(&mut self, jobs: &mut RenderJobs, transform: &Transform) -> Option<Block> {
-        if let Some(val) = self.object.to_object(jobs, transform) {
-            Some(Block::new(val, self.behavior))
-        } else {
-            None
-        }
+    pub fn to_block(&self, transform: &Transform) -> Option<Block> {
+        self.object
+            .to_object(transform)
+            .map(|val| Block::new(val, self.behavior))
     }
     pub fn behavior(mut self, behavior: Behavior) -> Self {
         self.behavior = behavior;
         self
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CollideAction {
     Advance,
     Kill,
@@ -295,6 +337,17 @@ impl Block {
     pub fn interactable(&self) -> bool {
         self.behavior == Behavior::None
     }
+    // whether things that walk around (enemies) can stand on this block, and get stopped by it
+    pub fn solid(&self) -> bool {
+        matches!(
+            self.behavior,
+            Behavior::Stop
+                | Behavior::Stick
+                | Behavior::Move(_)
+                | Behavior::Wrap
+                | Behavior::Portal
+        )
+    }
     pub fn priority(&mut self) -> usize {
         match self.behavior {
             Behavior::None => 0, // first: phase transitioners that must happen
@@ -340,7 +393,6 @@ impl Block {
         player: &mut Object,
         direction: Direction,
         ctrl: &mut Controls,
-        renderer: &mut RenderJobs,
     ) -> CollideAction {
         match self.behavior {
             Behavior::Stop => match direction {
@@ -452,24 +504,18 @@ impl Block {
             }
             Behavior::Flip => {
                 if ctrl.can_be_flipped_y == 0 && ctrl.gravity_y == -1.0 {
-                    ctrl.down_internal(
-                        player,
-                        renderer.get_job_mut(player.job_id).expect("safe unwrap"),
-                    );
+                    ctrl.down_internal();
                     player.y_speed = -1.0;
                     player.y_pos -= FUDGE * 4.0;
                 } else if ctrl.can_be_flipped_y == 0 {
-                    ctrl.up_internal(
-                        player,
-                        renderer.get_job_mut(player.job_id).expect("safe unwrap"),
-                    );
+                    ctrl.up_internal();
                     player.y_speed = 1.0;
                     player.y_pos += FUDGE * 4.0;
                 }
                 ctrl.can_be_flipped_y = 10;
             }
         }
-        return CollideAction::None;
+        CollideAction::None
     }
     pub fn new(object: Object, behavior: Behavior) -> Block {
         Block { object, behavior }
@@ -491,11 +537,16 @@ impl Block {
             x <<= 1;
             y <<= 1;
         }
-        Partition { x, y }
+        // it's measured against the first screen only
+        Partition {
+            x,
+            y,
+            screens: [1, 1],
+        }
     }
 }
 // how the block interacts with the player on touch
-#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, Debug)]
 pub enum Behavior {
     Stop,
     Kill,
@@ -503,6 +554,7 @@ pub enum Behavior {
     Advance,
     Wrap,
     Portal,
+    #[default]
     None,
     Stick,
     Water,
@@ -528,3 +580,26 @@ impl Direction {
 }
 
 //NoWay1234
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a tile wide span across, starting at x
+    fn span(x: f64) -> Partition {
+        let screen = WINDOW_X as f64;
+        Partition {
+            x: partition_bits(x, GRID_SIZE, screen),
+            y: 1,
+            screens: [screen_bits(x, GRID_SIZE, screen), 1],
+        }
+    }
+    #[test]
+    fn faraway_screens_dont_share_partitions() {
+        let screen = WINDOW_X as f64;
+        assert!(span(100.0).collides(&span(100.0 + GRID_SIZE / 2.0)));
+        assert!(!span(100.0).collides(&span(100.0 + screen * 3.0)));
+        // next to the edge of a screen, the next screen over is close by
+        assert!(span(screen - GRID_SIZE).collides(&span(screen + 1.0)));
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-#[derive(Eq, Hash, PartialEq, Clone, Copy)]
+#[derive(Eq, Hash, PartialEq, Clone, Copy, PartialOrd, Ord)]
 pub struct PartitionMapID(pub usize);
 pub struct PartitionMap {
     pub player: Partition,
@@ -24,12 +24,19 @@ impl PartitionMap {
         self.map.insert(id, partition);
         id
     }
-    pub fn remove(&mut self, id: PartitionMapID) {
-        self.map.remove(&id);
-        self.cache.push(id);
-    }
+    // moves an entry to a new partition, keeping the player's cache up to date
     pub fn set(&mut self, id: PartitionMapID, new: Partition) {
-        self.map.insert(id, new);
+        let was_cached = self
+            .map
+            .insert(id, new)
+            .is_some_and(|old| old.collides(&self.player));
+        let is_cached = new.collides(&self.player);
+        if is_cached && !was_cached {
+            self.cache.push(id);
+            self.cache.sort();
+        } else if was_cached && !is_cached {
+            self.cache.retain(|x| *x != id);
+        }
     }
     pub fn set_player(&mut self, map: Partition) {
         if self.player != map {
@@ -44,6 +51,8 @@ impl PartitionMap {
                 self.cache.push(*id);
             }
         }
+        // hash map order changes from run to run; keep collisions resolving in the same order
+        self.cache.sort();
     }
     pub fn clear(&mut self) {
         self.map.clear();
@@ -55,9 +64,15 @@ impl PartitionMap {
 pub struct Partition {
     pub x: u64, // simple partition indicator. In binary, 1 means the object is inside this partition, 0 means it is not.
     pub y: u64,
+    // Which screens ([across, down]) it's on, as bits that repeat every 64 screens. The partitions
+    // above repeat every screen, so this keeps things on faraway screens of a scrolling level apart.
+    pub screens: [u64; 2],
 }
 impl Partition {
     pub fn collides(&self, other: &Partition) -> bool {
-        (self.x & other.x) != 0 && (self.y & other.y) != 0
+        (self.x & other.x) != 0
+            && (self.y & other.y) != 0
+            && (self.screens[0] & other.screens[0]) != 0
+            && (self.screens[1] & other.screens[1]) != 0
     }
 }
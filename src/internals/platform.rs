@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::consts::NUM_TIMES_F64;
+
+use super::{
+    object::{Behavior, Block, BlockTemplate, Object, Transform},
+    partition_map::{PartitionMap, PartitionMapID},
+};
+
+// A platform as it's written in a level file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlatformTemplate {
+    pub block: BlockTemplate, // what the platform looks like. Its position is ignored in favor of the path's first point.
+    pub path: Vec<[f64; 2]>,  // tile positions that the platform travels between, in order
+    pub speed: f64,           // tiles per tick
+    pub looping: bool, // if true, goes from the last point back to the first. Otherwise, turns around.
+}
+impl PlatformTemplate {
+    // creates the block for the platform and the platform that moves it.
+    pub fn to_platform(&self, transform: &Transform) -> Option<(Block, Platform)> {
+        let start = self.path.first()?;
+        let block = self
+            .block
+            .clone()
+            .object
+            .x_pos(start[0])
+            .y_pos(start[1])
+            .to_object(transform)
+            .map(|x| Block::new(x, self.block.behavior))?;
+        let path = self
+            .path
+            .iter()
+            .map(|x| {
+                [
+                    (x[0] + transform.tile_offset[0]) * transform.tile_size[0],
+                    (x[1] + transform.tile_offset[1]) * transform.tile_size[1],
+                ]
+            })
+            .collect();
+        Some((
+            block,
+            Platform {
+                id: PartitionMapID(0),
+                path,
+                target: 0,
+                step: 1,
+                speed: self.speed * transform.tile_size[0],
+                looping: self.looping,
+            },
+        ))
+    }
+}
+
+// Moves a block along a path, dragging the player along with it if they're standing on it.
+pub struct Platform {
+    pub id: PartitionMapID, // which of the interactables is this platform
+    pub path: Vec<[f64; 2]>,
+    pub target: usize, // which point on the path it's heading to
+    pub step: isize,   // forwards (1) or backwards (-1) along the path
+    pub speed: f64,    // pixels per tick
+    pub looping: bool,
+}
+impl Platform {
+    // Moves the platform by one collision check's worth, and keeps its partition up to date.
+    pub fn tick(
+        &mut self,
+        interactables: &mut [Option<Block>],
+        partitioner: &mut PartitionMap,
+        player: Option<&mut Object>,
+    ) {
+        let block = match &mut interactables[self.id.0] {
+            Some(val) => val,
+            None => return,
+        };
+        let movement = self.movement(&block.object);
+        if let Some(player) = player {
+            if Platform::carries(block, player) {
+                player.x_pos += movement[0];
+                player.y_pos += movement[1];
+            }
+        }
+        block.object.x_pos += movement[0];
+        block.object.y_pos += movement[1];
+        block.object.partition = block.object.partition();
+        partitioner.set(self.id, block.object.partition);
+    }
+    // how far to move this check, moving on to the next point if the current one is reached
+    fn movement(&mut self, object: &Object) -> [f64; 2] {
+        let target = self.path[self.target];
+        let delta = [target[0] - object.x_pos, target[1] - object.y_pos];
+        let distance = (delta[0] * delta[0] + delta[1] * delta[1]).sqrt();
+        let max = self.speed / NUM_TIMES_F64;
+        if distance <= max {
+            self.next_target();
+            return delta;
+        }
+        [delta[0] * max / distance, delta[1] * max / distance]
+    }
+    fn next_target(&mut self) {
+        if self.path.len() < 2 {
+            return;
+        }
+        let next = self.target as isize + self.step;
+        if next >= 0 && (next as usize) < self.path.len() {
+            self.target = next as usize;
+        } else if self.looping {
+            self.target = 0;
+        } else {
+            self.step = -self.step;
+            self.target = (self.target as isize + self.step) as usize;
+        }
+    }
+    // whether the player is standing on the platform (from either side, gravity can flip) or stuck to it
+    fn carries(block: &mut Block, player: &mut Object) -> bool {
+        let [up, down, left, right] = block.collides(player);
+        match block.behavior {
+            Behavior::Stop => up || down,
+            Behavior::Stick => up || down || left || right,
+            _ => false,
+        }
+    }
+}
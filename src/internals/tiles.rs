@@ -0,0 +1,265 @@
+use std::{collections::HashMap, fmt, path::Path, sync::OnceLock};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    consts::{CONTENT_LAYER, TILES_PATH},
+    render::{
+        assets::asset_path, rect::Rect, texture::ImageRenderer, texture::TextureID, RenderJob,
+    },
+};
+
+use super::{
+    levels::GridSpace,
+    object::{Behavior, ObjectTemplate},
+};
+
+static TILES: OnceLock<TileRegistry> = OnceLock::new();
+
+// The tile definitions everything uses. The game reads them when it starts (see load_tiles), so
+// this only reads them itself (and panics if they're broken) when that was skipped, as in tests.
+pub fn tiles() -> &'static TileRegistry {
+    load_tiles().unwrap_or_else(|e| panic!("Error loading tile definitions: {}", e))
+}
+// reads the tile definitions from the assets folder, if they haven't been already
+pub fn load_tiles() -> Result<&'static TileRegistry, String> {
+    if let Some(tiles) = TILES.get() {
+        return Ok(tiles);
+    }
+    let registry = TileRegistry::load(asset_path(TILES_PATH))?;
+    Ok(TILES.get_or_init(|| registry))
+}
+
+// what the game does with a tile besides (maybe) turning it into a block
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum TileKind {
+    // an ordinary block, using the tile's behavior
+    #[default]
+    Block,
+    // there is nothing here
+    Empty,
+    // a player starts here
+    Player,
+    // a block that walks around (see Enemy)
+    Enemy,
+}
+
+// Everything there is to know about one type of tile.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TileDef {
+    pub id: usize,    // what the tile is stored as in binary level files. Ids go 0, 1, 2...
+    pub name: String, // what the tile is stored as in json level files
+    pub symbol: char, // what the tile is drawn as in ascii level files (see medit::ascii)
+    #[serde(default)]
+    pub kind: TileKind,
+    #[serde(default)]
+    pub behavior: Behavior,
+    pub color: [f32; 4],
+    #[serde(default)]
+    pub texture: Option<TextureID>,
+    // How the tile looks in game, if that's different from the editor. Missing parts are filled in from color/texture.
+    #[serde(default)]
+    pub template: Option<ObjectTemplate>,
+    // [column, row] inside the editor's guide. Tiles without one can't be picked from the guide.
+    #[serde(default)]
+    pub palette: Option<[u32; 2]>,
+}
+impl TileDef {
+    pub fn job(&self, bounds: [f64; 4], color: [f32; 4]) -> RenderJob {
+        match self.texture {
+            Some(texture) => ImageRenderer::new(bounds, color, texture),
+            None => Rect::new(color, bounds),
+        }
+    }
+    // the template used to create the tile in game
+    pub fn template(&self) -> ObjectTemplate {
+        let mut res = self.template.clone().unwrap_or_default();
+        res.or(&ObjectTemplate::new()
+            .width(1.0)
+            .height(1.0)
+            .job(self.job([0.0; 4], self.color))
+            .layer(CONTENT_LAYER));
+        res
+    }
+}
+
+pub struct TileRegistry {
+    pub tiles: Vec<TileDef>, // indexed by id
+    by_name: HashMap<String, GridSpace>,
+    by_symbol: HashMap<char, GridSpace>,
+    empty: GridSpace,
+    start: GridSpace,
+}
+impl TileRegistry {
+    pub fn load(path: impl AsRef<Path>) -> Result<TileRegistry, String> {
+        let data = std::fs::read(path).map_err(|x| x.to_string())?;
+        TileRegistry::from_json(&data)
+    }
+    // reads a list of tile definitions, checking that they make sense together
+    pub fn from_json(data: &[u8]) -> Result<TileRegistry, String> {
+        let mut tiles: Vec<TileDef> = serde_json::from_slice(data).map_err(|x| x.to_string())?;
+        tiles.sort_by_key(|x| x.id);
+        let mut by_name = HashMap::new();
+        let mut by_symbol = HashMap::new();
+        for (i, tile) in tiles.iter().enumerate() {
+            if i > 0 && tile.id == tiles[i - 1].id {
+                return Err(format!("There's more than one tile with id {}", tile.id));
+            }
+            if tile.id != i {
+                return Err(format!("Tile ids must go 0, 1, 2... (missing {})", i));
+            }
+            if by_name.insert(tile.name.clone(), GridSpace(i)).is_some() {
+                return Err(format!("There's more than one tile called {}", tile.name));
+            }
+            // whitespace and # mean something else in ascii levels
+            if tile.symbol.is_whitespace() || tile.symbol == '#' {
+                return Err(format!("{} can't be drawn as {:?}", tile.name, tile.symbol));
+            }
+            if by_symbol.insert(tile.symbol, GridSpace(i)).is_some() {
+                return Err(format!(
+                    "There's more than one tile drawn as {}",
+                    tile.symbol
+                ));
+            }
+        }
+        let find = |kind: TileKind| {
+            tiles
+                .iter()
+                .position(|x| x.kind == kind)
+                .map(GridSpace)
+                .ok_or(format!("There needs to be a tile of kind {:?}", kind))
+        };
+        let empty = find(TileKind::Empty)?;
+        let start = find(TileKind::Player)?;
+        Ok(TileRegistry {
+            tiles,
+            by_name,
+            by_symbol,
+            empty,
+            start,
+        })
+    }
+    pub fn get(&self, tile: GridSpace) -> &TileDef {
+        &self.tiles[tile.0]
+    }
+    pub fn named(&self, name: &str) -> Option<GridSpace> {
+        self.by_name.get(name).copied()
+    }
+    pub fn with_symbol(&self, symbol: char) -> Option<GridSpace> {
+        self.by_symbol.get(&symbol).copied()
+    }
+    // the tile at a position in the editor's guide
+    pub fn at_palette(&self, pos: [usize; 2]) -> Option<GridSpace> {
+        self.tiles
+            .iter()
+            .position(|x| x.palette == Some([pos[0] as u32, pos[1] as u32]))
+            .map(GridSpace)
+    }
+    pub fn empty(&self) -> GridSpace {
+        self.empty
+    }
+    pub fn start(&self) -> GridSpace {
+        self.start
+    }
+}
+
+// Tiles are stored by name in text formats and by id in binary ones.
+impl Serialize for GridSpace {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&tiles().get(*self).name)
+        } else {
+            serializer.serialize_u64(self.0 as u64)
+        }
+    }
+}
+impl<'de> Deserialize<'de> for GridSpace {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(GridSpaceVisitor)
+        } else {
+            deserializer.deserialize_u64(GridSpaceVisitor)
+        }
+    }
+}
+struct GridSpaceVisitor;
+impl<'de> Visitor<'de> for GridSpaceVisitor {
+    type Value = GridSpace;
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("the name or id of a tile")
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<GridSpace, E> {
+        tiles()
+            .named(v)
+            .ok_or_else(|| E::custom(format!("unknown tile {}", v)))
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<GridSpace, E> {
+        if (v as usize) < tiles().tiles.len() {
+            Ok(GridSpace(v as usize))
+        } else {
+            Err(E::custom(format!("unknown tile id {}", v)))
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    // the bundled definitions, changed somehow
+    fn registry_with(change: impl FnOnce(&mut Vec<Value>)) -> Result<TileRegistry, String> {
+        let data = std::fs::read(asset_path(TILES_PATH)).unwrap();
+        let mut tiles: Vec<Value> = serde_json::from_slice(&data).unwrap();
+        change(&mut tiles);
+        TileRegistry::from_json(&serde_json::to_vec(&tiles).unwrap())
+    }
+    fn kind(tiles: &mut [Value], kind: &str) -> usize {
+        tiles.iter().position(|x| x["kind"] == kind).unwrap()
+    }
+    #[test]
+    fn reads_the_bundled_tiles() {
+        let registry = registry_with(|_| {}).unwrap();
+        assert_eq!(registry.get(registry.empty()).kind, TileKind::Empty);
+        assert_eq!(registry.get(registry.start()).kind, TileKind::Player);
+    }
+    #[test]
+    fn refuses_duplicates() {
+        let id = registry_with(|x| x[2]["id"] = json!(1));
+        assert_eq!(id.err().unwrap(), "There's more than one tile with id 1");
+        let name = registry_with(|x| x[2]["name"] = x[1]["name"].clone());
+        assert!(name
+            .err()
+            .unwrap()
+            .starts_with("There's more than one tile called"));
+        let symbol = registry_with(|x| x[2]["symbol"] = x[1]["symbol"].clone());
+        assert!(symbol
+            .err()
+            .unwrap()
+            .starts_with("There's more than one tile drawn as"));
+    }
+    #[test]
+    fn refuses_gaps_in_ids() {
+        let gap = registry_with(|x| x[2]["id"] = json!(x.len()));
+        assert_eq!(
+            gap.err().unwrap(),
+            "Tile ids must go 0, 1, 2... (missing 2)"
+        );
+    }
+    #[test]
+    fn needs_empty_and_player_tiles() {
+        for needed in ["Empty", "Player"] {
+            let missing = registry_with(|x| {
+                let i = kind(x, needed);
+                x[i]["kind"] = json!("Block");
+            });
+            assert_eq!(
+                missing.err().unwrap(),
+                format!("There needs to be a tile of kind {}", needed)
+            );
+        }
+    }
+}
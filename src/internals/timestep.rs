@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use crate::consts::{MAX_TICKS_PER_FRAME, TICK_LENGTH};
+
+// Decides how many game ticks to run each frame, so the game runs at the same speed no matter the framerate.
+pub struct Timestep {
+    last_time: Instant,
+    accumulator: Duration, // time that has passed but hasn't been simulated yet
+}
+impl Timestep {
+    pub fn new() -> Timestep {
+        Timestep {
+            last_time: Instant::now(),
+            accumulator: Duration::ZERO,
+        }
+    }
+    // the number of ticks that are due since the last call.
+    pub fn ticks(&mut self) -> u32 {
+        let now = Instant::now();
+        let elapsed = now - self.last_time;
+        self.last_time = now;
+        self.advance(elapsed)
+    }
+    // the number of ticks that are due once some more time has passed
+    fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+        let mut res = 0;
+        while self.accumulator >= TICK_LENGTH {
+            self.accumulator -= TICK_LENGTH;
+            res += 1;
+        }
+        if res > MAX_TICKS_PER_FRAME {
+            // we've fallen too far behind to ever catch up. Slow down instead of freezing.
+            res = MAX_TICKS_PER_FRAME;
+        }
+        res
+    }
+    // how far along we are between the last tick and the next one, from 0 to 1.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / TICK_LENGTH.as_secs_f64()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_at_a_fixed_rate() {
+        let mut timestep = Timestep::new();
+        // faster frames than ticks: most frames run none
+        let ticks: u32 = (0..20).map(|_| timestep.advance(TICK_LENGTH / 2)).sum();
+        assert_eq!(ticks, 10);
+        assert!(timestep.alpha() < 1e-6);
+        // slower frames than ticks: some frames run more than one
+        assert_eq!(timestep.advance(TICK_LENGTH * 5 / 2), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+    }
+    #[test]
+    fn slows_down_when_far_behind() {
+        let mut timestep = Timestep::new();
+        assert_eq!(timestep.advance(TICK_LENGTH * 100), MAX_TICKS_PER_FRAME);
+    }
+}
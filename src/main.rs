@@ -4,12 +4,28 @@ use std::{
 };
 
 use consts::{
-    ASSETS_FOLDER, DEFAULT_FONT_PATH, MEDIT_WINDOW_X, MEDIT_WINDOW_Y, WINDOW_X, WINDOW_Y,
+    DEFAULT_PACK_PATH, MEDIT_WINDOW_X, MEDIT_WINDOW_Y, SNAPSHOT_TOLERANCE, TICK_LENGTH, TILES_PATH,
+    WINDOW_X, WINDOW_Y,
+};
+use input::replay::Replay;
+use internals::{
+    levels::{Level, Levels, PackManifest},
+    tiles,
+    timestep::Timestep,
+    Game,
+};
+use medit::{
+    migrate::{self, SCHEMA_VERSION},
+    recovery, validate, IOMap, Map, MapFormat,
+};
+use opengl_graphics::{GlyphCache, TextureSettings};
+use piston_window::AdvancedWindow;
+use render::{
+    assets::{self, asset_path},
+    raster::{Raster, RasterGlyphCache},
+    texture::TextureBuffer,
+    RenderJobs, Window,
 };
-use internals::Game;
-use medit::Map;
-use opengl_graphics::{Texture, TextureSettings};
-use render::Window;
 
 #[allow(dead_code)]
 mod consts;
@@ -18,99 +34,333 @@ mod internals;
 pub mod medit;
 mod render;
 
+const USAGE: &str = "Usage:
+    play [level pack]
+    edit [level path]
+    record [replay path] [level pack]
+    replay [replay path] [level pack]
+    simulate [level path or level pack] [frames or replay path]
+    snapshot [level path or level pack] [frames or replay path] [image path] [reference image path]
+    convert [level path] [new path] [json, binary or ascii]
+    upgrade [level paths...]
+    validate [level paths or level packs...]";
+
+enum Mode {
+    Play(PathBuf),
+    Edit(PathBuf),
+    Record(PathBuf, PathBuf),
+    Replay(PathBuf, PathBuf),
+}
+
 fn main() {
     // each frame...
-    let mut args = std::env::args();
-    let x: u32;
-    let y: u32;
-    let path = if args.len() > 2 {
-        args.next();
-        if args.next().unwrap() == "edit".to_string() {
-            let path: String = args.next().expect("Safe unwrap");
-            let path = PathBuf::try_from(path).expect("Please enter a valid path!");
-            x = MEDIT_WINDOW_X;
-            y = MEDIT_WINDOW_Y;
-            Some(path)
-        } else {
-            x = WINDOW_X;
-            y = WINDOW_Y;
-            None
+    let args: Vec<String> = std::env::args().collect();
+    // every mode needs the tiles, and a broken definitions file is better caught here than later
+    if let Err(e) = tiles::load_tiles() {
+        exit_with(&format!("{}: {}", TILES_PATH, e));
+    }
+    let mode = match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("simulate"), Some(path)) => {
+            // runs a level (or a whole level pack) without a window: simulate [level path] [frames or replay path]
+            simulate(load_levels(path), &load_replay(args.get(3)));
+            return;
+        }
+        (Some("snapshot"), Some(path)) => {
+            // Like simulate, but draws the last frame into an image without a graphics card, and
+            // checks it against a reference image if there is one:
+            // snapshot [level path] [frames or replay path] [image path] [reference image path]
+            let replay = load_replay(args.get(3));
+            let out = args.get(4).expect("Please enter where to save the image!");
+            let reference = args.get(5).map(Path::new);
+            if let Err(e) = snapshot(load_levels(path), &replay, Path::new(out), reference) {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        (Some("convert"), Some(path)) => {
+            // rewrites a level file in another format: convert [level path] [new path] [json, binary or ascii]
+            let out = args.get(3).expect("Please enter where to save the level!");
+            let format = args
+                .get(4)
+                .map_or(Some(MapFormat::Binary), |x| MapFormat::from_name(x));
+            let format = format.expect("The format must be json, binary or ascii!");
+            let (io_map, _) = IOMap::load(path).expect("Error loading level!");
+            io_map.save(out, format).expect("Error saving level!");
+            return;
+        }
+        (Some("upgrade"), Some(_)) => {
+            // rewrites older level files at the current version: upgrade [level paths...]
+            for path in &args[2..] {
+                match migrate::upgrade_file(path) {
+                    Ok(Some(version)) => println!(
+                        "Upgraded {} from version {} to {}",
+                        path, version, SCHEMA_VERSION
+                    ),
+                    Ok(None) => println!("{} is already up to date", path),
+                    Err(e) => println!("Upgrading {} failed: {}", path, e),
+                }
+            }
+            return;
+        }
+        (Some("validate"), Some(_)) => {
+            // checks levels for problems: validate [level paths or level packs...]
+            let mut failed = false;
+            for path in &args[2..] {
+                let path = Path::new(path);
+                if path.extension().is_none_or(|x| x != "json") {
+                    failed |= !validate_level(path, false);
+                    continue;
+                }
+                // every level in a pack. Endings don't need a way out.
+                match PackManifest::load(path) {
+                    Ok(manifest) => {
+                        let folder = path.parent().unwrap_or(Path::new(""));
+                        for entry in &manifest.levels {
+                            failed |= !validate_level(&folder.join(&entry.path), entry.ending);
+                        }
+                    }
+                    Err(e) => {
+                        println!("{}: error: Loading failed: {}", path.display(), e);
+                        failed = true;
+                    }
+                }
+            }
+            std::process::exit(failed as i32);
+        }
+        (Some("edit"), Some(path)) => Mode::Edit(PathBuf::from(path)),
+        // record/replay [replay path] [level pack]
+        (Some("record"), Some(path)) => Mode::Record(PathBuf::from(path), pack_path(args.get(3))),
+        (Some("replay"), Some(path)) => Mode::Replay(PathBuf::from(path), pack_path(args.get(3))),
+        // play [level pack]
+        (Some("play"), pack) => Mode::Play(pack_path(pack)),
+        (None, _) => Mode::Play(pack_path(None)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
         }
-    } else {
-        x = WINDOW_X;
-        y = WINDOW_Y;
-        None
+    };
+    let (x, y) = match mode {
+        Mode::Edit(_) => (MEDIT_WINDOW_X, MEDIT_WINDOW_Y),
+        _ => (WINDOW_X, WINDOW_Y),
     };
 
-    // sets a font path
-    let mut font_path = PathBuf::new();
-    // adds to the font path
-    font_path.push(ASSETS_FOLDER);
-    font_path.push(DEFAULT_FONT_PATH);
-    // creates a font
-    let font = std::fs::read(&font_path).expect("Erroring out for now");
-    // adds the glyphs
-    let glyphs =
-        opengl_graphics::GlyphCache::from_bytes(&font, (), TextureSettings::new()).unwrap();
-    // creates a new window based on that font
-    let mut window = Window::new(vec![glyphs], x, y);
-    window.textures.add(
-        Texture::from_path(
-            Path::new("assets\\images\\spikes.png"),
-            &TextureSettings::new(),
-        )
-        .expect("File not found!"),
-    );
-    window.textures.add(
-        Texture::from_path(
-            Path::new("assets\\images\\goal.png"),
-            &TextureSettings::new(),
-        )
-        .expect("File not found!"),
-    );
-    window.textures.add(
-        Texture::from_path(
-            Path::new("assets\\images\\wrap.png"),
-            &TextureSettings::new(),
-        )
-        .expect("File not found!"),
-    );
-    window.textures.add(
-        Texture::from_path(
-            Path::new("assets\\images\\transition.png"),
-            &TextureSettings::new(),
-        )
-        .expect("File not found!"),
-    );
-    window.textures.add(
-        Texture::from_path(
-            Path::new("assets\\images\\conveyorL.png"),
-            &TextureSettings::new(),
-        )
-        .expect("File not found!"),
-    );
-    window.textures.add(
-        Texture::from_path(
-            Path::new("assets\\images\\conveyorR.png"),
-            &TextureSettings::new(),
-        )
-        .expect("File not found!"),
-    );
-    if let Some(path) = path {
-        let mut map = Map::load(path.clone(), &mut window.jobs).unwrap_or_else(|_| {
-            println!("Error loading map!");
-            Map::new(path, &mut window.jobs)
-        });
-        while window.run_loop_iteration() {
-            map.tick(&mut window.jobs, &mut window.input);
+    // every texture and font is listed in the asset manifest
+    let manifest = assets::manifest().unwrap_or_else(|e| exit_with(&e));
+    let fonts = manifest.read_fonts().unwrap_or_else(|e| exit_with(&e));
+    let glyphs = fonts
+        .iter()
+        .zip(&manifest.fonts)
+        .map(|(data, entry)| {
+            GlyphCache::from_bytes(data, (), TextureSettings::new())
+                .unwrap_or_else(|_| exit_with(&format!("{} isn't a usable font", entry.path)))
+        })
+        .collect();
+    // creates a new window based on those fonts
+    let mut window = Window::new(glyphs, x, y);
+    if let Err(e) = window.textures.load_manifest(manifest) {
+        exit_with(&e);
+    }
+    match mode {
+        Mode::Edit(path) => {
+            let mut map = Map::load(path.clone(), &mut window.jobs).unwrap_or_else(|e| {
+                let mut map = Map::new(path, &mut window.jobs);
+                map.message(format!("Starting a new level, since loading failed: {}", e));
+                map.check_recovery();
+                map
+            });
+            recovery::install_hook();
+            let mut playtesting = false;
+            while window.run_loop_iteration() {
+                if map.tick(&mut window.jobs, &mut window.input) {
+                    break;
+                }
+                // a playtest only lets go of input once a tick has used it, or presses on frames
+                // without a tick would be lost
+                if !(playtesting && map.playtesting()) {
+                    window.input.reset();
+                }
+                // the game is drawn at its own size, so the window changes size while playtesting
+                if map.playtesting() != playtesting {
+                    playtesting = map.playtesting();
+                    let size = if playtesting {
+                        [WINDOW_X, WINDOW_Y]
+                    } else {
+                        [MEDIT_WINDOW_X, MEDIT_WINDOW_Y]
+                    };
+                    window.window.set_size(size);
+                }
+            }
+            // closing the window doesn't lose anything that wasn't saved
+            if let Err(e) = recovery::flush() {
+                println!("Writing the recovery file failed: {}", e);
+            }
         }
-    } else {
-        let mut game = Game::new(&mut window.jobs);
-        while window.run_loop_iteration() {
-            game.tick(&mut window.jobs, &mut window.input);
+        Mode::Play(pack) => {
+            let mut game = Game::new(load_pack(&pack));
+            let mut timestep = Timestep::new();
+            let mut shown_level = None;
+            while window.run_loop_iteration() {
+                for _ in 0..timestep.ticks() {
+                    game.tick(&window.input);
+                    window.input.reset();
+                }
+                announce_level(&game, &mut shown_level);
+                game.render(&mut window.jobs, timestep.alpha());
+            }
+        }
+        Mode::Record(path, pack) => {
+            let mut game = Game::new(load_pack(&pack));
+            let mut timestep = Timestep::new();
+            let mut shown_level = None;
+            window.input.record();
+            while window.run_loop_iteration() {
+                for _ in 0..timestep.ticks() {
+                    game.tick(&window.input);
+                    window.input.reset();
+                }
+                announce_level(&game, &mut shown_level);
+                game.render(&mut window.jobs, timestep.alpha());
+            }
+            let replay = window.input.stop_recording().expect("Safe unwrap");
+            if let Err(e) = replay.save(&path) {
+                println!("Saving replay failed: {}", e);
+            } else {
+                println!("Saved replay to {}", path.display());
+            }
+        }
+        Mode::Replay(path, pack) => {
+            let replay = Replay::load(&path).expect("Error loading replay!");
+            let mut player = replay.player();
+            let mut game = Game::new(load_pack(&pack));
+            let mut timestep = Timestep::new();
+            // the window's own input is ignored; everything comes from the replay
+            'replay: while window.run_loop_iteration() {
+                for _ in 0..timestep.ticks() {
+                    match player.next_frame() {
+                        Some(input) => game.tick(input),
+                        None => break 'replay,
+                    };
+                }
+                game.render(&mut window.jobs, timestep.alpha());
+            }
         }
     }
 }
+// the level pack given on the command line, or the default one.
+fn pack_path(arg: Option<&String>) -> PathBuf {
+    match arg {
+        Some(path) => PathBuf::from(path),
+        None => asset_path(DEFAULT_PACK_PATH),
+    }
+}
+// for assets the game can't go without
+fn exit_with(error: &str) -> ! {
+    eprintln!("Error loading assets: {}", error);
+    std::process::exit(1);
+}
+// Checks a level, printing everything that's wrong with it. Returns false if it has any errors.
+fn validate_level(path: &Path, ending: bool) -> bool {
+    let report = match IOMap::load(path) {
+        Ok((io_map, _)) => validate::validate(&io_map, ending),
+        Err(e) => validate::Report {
+            errors: vec![format!("Loading failed: {}", e)],
+            warnings: Vec::new(),
+        },
+    };
+    for error in &report.errors {
+        println!("{}: error: {}", path.display(), error);
+    }
+    for warning in &report.warnings {
+        println!("{}: warning: {}", path.display(), warning);
+    }
+    if report.errors.is_empty() && report.warnings.is_empty() {
+        println!("{}: ok", path.display());
+    }
+    report.errors.is_empty()
+}
+// a level pack if it's a json file, otherwise a single level
+fn load_levels(path: &str) -> Levels {
+    let path = Path::new(path);
+    if path.extension().is_some_and(|x| x == "json") {
+        load_pack(path)
+    } else {
+        Levels::from_level(Level::load(path).expect("Error loading level!"))
+    }
+}
+// a replay file, or a number of frames with no input
+fn load_replay(arg: Option<&String>) -> Replay {
+    let arg = arg.expect("Please enter a number of frames or a replay!");
+    match arg.parse() {
+        Ok(frames) => Replay {
+            events: Vec::new(),
+            frames,
+        },
+        Err(_) => Replay::load(arg).expect("Error loading replay!"),
+    }
+}
+fn load_pack(path: &Path) -> Levels {
+    Levels::load(path)
+        .unwrap_or_else(|e| exit_with(&format!("level pack {}: {}", path.display(), e)))
+}
+// prints the name of the level whenever a new one is entered
+fn announce_level(game: &Game, shown_level: &mut Option<usize>) {
+    if *shown_level != Some(game.current_level) {
+        *shown_level = Some(game.current_level);
+        println!("{}", game.levels.levels[game.current_level].name);
+    }
+}
+// runs levels headlessly, feeding it a replay and printing everything that happens.
+fn simulate(levels: Levels, replay: &Replay) {
+    let mut game = Game::new(levels);
+    for (frame, report) in game.simulate_replay(replay).into_iter().enumerate() {
+        println!(
+            "Frame {}: level {}, screen {:?}, player {:?}, actions {:?}",
+            frame + 1,
+            report.level,
+            report.screen,
+            report.player_pos,
+            report.actions
+        );
+    }
+}
+// Runs levels headlessly like simulate, drawing every tick on the CPU as the game would, then saves
+// the last frame. If there's a reference image, the frame has to match it.
+fn snapshot(
+    levels: Levels,
+    replay: &Replay,
+    out: &Path,
+    reference: Option<&Path>,
+) -> Result<(), String> {
+    let manifest = assets::manifest()?;
+    let fonts = manifest.read_fonts()?;
+    let mut glyphs = fonts
+        .iter()
+        .zip(&manifest.fonts)
+        .map(|(data, entry)| {
+            RasterGlyphCache::from_bytes(data, (), TextureSettings::new())
+                .map_err(|_| format!("{} isn't a usable font", entry.path))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut textures = TextureBuffer::new();
+    textures.load_manifest(manifest)?;
+    let mut game = Game::new(levels);
+    let mut jobs = RenderJobs::new();
+    let mut player = replay.player();
+    game.render(&mut jobs, 1.0);
+    while let Some(input) = player.next_frame() {
+        game.tick(input);
+        game.render(&mut jobs, 1.0);
+        jobs.advance(TICK_LENGTH.as_secs_f64());
+    }
+    let mut frame = Raster::new(WINDOW_X, WINDOW_Y);
+    frame.draw(&mut jobs, &mut glyphs, &textures);
+    frame.save(out)?;
+    match reference {
+        Some(reference) => frame.compare(reference, SNAPSHOT_TOLERANCE),
+        None => Ok(()),
+    }
+}
 // debug function
 #[allow(dead_code)]
 fn wait_for_input() {
@@ -1,46 +1,182 @@
-use std::{fs, mem, path::PathBuf};
+pub mod ascii;
+pub mod clipboard;
+pub mod history;
+pub mod hud;
+pub mod migrate;
+pub mod minimap;
+pub mod objects;
+pub mod playtest;
+pub mod recovery;
+pub mod text;
+pub mod tools;
+pub mod validate;
+pub mod view;
 
+use std::{
+    fs, mem,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use bincode::Options;
 use piston::Key;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     consts::{
-        self, CONTENT_LAYER, LEFT_MOUSE, MEDIT_GUIDE_SIZE, MEDIT_TILES, MEDIT_TILE_SIZE,
-        RIGHT_MOUSE,
+        self, BLACK, CONTENT_LAYER, FAINT_WHITE, GRID_SIZE, LEFT_MOUSE, MEDIT_AUTOSAVE_TIME,
+        MEDIT_GUIDE_SIZE, MEDIT_TILES, MEDIT_TILE_SIZE, MIDDLE_MOUSE, RIGHT_MOUSE,
     },
     input::InputVars,
-    internals::levels::{GridSpace, Level, LevelGrid},
-    render::{RenderJob, RenderJobID, RenderJobs},
+    internals::{
+        levels::{GridSpace, Level, LevelGrid},
+        object::BlockTemplate,
+        tiles::tiles,
+    },
+    render::{rect::Rect, RenderJob, RenderJobComponent, RenderJobID, RenderJobs},
 };
 
+use self::{
+    clipboard::Clipboard,
+    history::{Change, History, LevelDiff, Snapshot, TileEdit},
+    hud::Hud,
+    migrate::SCHEMA_VERSION,
+    minimap::Minimap,
+    objects::Drag,
+    playtest::Playtest,
+    text::Typing,
+    tools::Tool,
+    view::View,
+};
+
+// binary level files start with this, followed by the format version (a little-endian u32)
+pub const BINARY_MAGIC: &[u8; 4] = b"GAML";
+pub const BINARY_VERSION: u32 = 3;
+
+// how a level file is written
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapFormat {
+    Json,   // pretty printed, readable
+    Binary, // bincode (with variable length integers), after the magic header. Much smaller and faster to load.
+    Ascii,  // drawn out tile by tile, see ascii.rs
+}
+impl MapFormat {
+    // figures out the format of a level file from its first few bytes.
+    pub fn detect(data: &[u8]) -> MapFormat {
+        if data.starts_with(BINARY_MAGIC) {
+            MapFormat::Binary
+        } else if data.trim_ascii_start().starts_with(b"{") {
+            MapFormat::Json
+        } else {
+            MapFormat::Ascii
+        }
+    }
+    pub fn from_name(name: &str) -> Option<MapFormat> {
+        match name {
+            "json" => Some(MapFormat::Json),
+            "binary" => Some(MapFormat::Binary),
+            "ascii" => Some(MapFormat::Ascii),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct IOMap {
+    version: u32, // see migrate::SCHEMA_VERSION
     grid: Vec<Vec<LevelGrid>>,
     player_start: [usize; 4],
     size: [usize; 2],
+    scrolling: bool, // see Level::scrolling
 }
 impl IOMap {
     pub fn new() -> IOMap {
         let mut grid = LevelGrid::new();
-        grid.contents[0][0] = GridSpace::StartingLocation;
+        grid.contents[0][0] = GridSpace::start();
         IOMap {
+            version: SCHEMA_VERSION,
             grid: vec![vec![grid]],
             player_start: [0; 4],
             size: [1, 1],
+            scrolling: false,
+        }
+    }
+    // reads a level file, whichever format it's in.
+    pub fn load(path: impl AsRef<Path>) -> Result<(IOMap, MapFormat), String> {
+        IOMap::from_bytes(&fs::read(path).map_err(|x| x.to_string())?)
+    }
+    pub fn from_bytes(data: &[u8]) -> Result<(IOMap, MapFormat), String> {
+        let format = MapFormat::detect(data);
+        let io_map = match format {
+            MapFormat::Json | MapFormat::Ascii => {
+                let mut value = if format == MapFormat::Json {
+                    serde_json::from_slice(data).map_err(|x| x.to_string())?
+                } else {
+                    ascii::import(std::str::from_utf8(data).map_err(|x| x.to_string())?)?
+                };
+                // older levels are upgraded before being read
+                migrate::migrate(&mut value)?;
+                serde_json::from_value(value).map_err(|x| x.to_string())?
+            }
+            MapFormat::Binary => {
+                let version = migrate::binary_version(data)?;
+                let body = &data[BINARY_MAGIC.len() + 4..];
+                if version == BINARY_VERSION {
+                    bincode::DefaultOptions::new()
+                        .deserialize(body)
+                        .map_err(|x| x.to_string())?
+                } else {
+                    // older binary levels are read the way they were written, then upgraded
+                    let mut value = migrate::read_old_binary(version, body)?;
+                    migrate::migrate(&mut value)?;
+                    serde_json::from_value(value).map_err(|x| x.to_string())?
+                }
+            }
+        };
+        Ok((io_map, format))
+    }
+    pub fn save(&self, path: impl AsRef<Path>, format: MapFormat) -> Result<(), String> {
+        fs::write(path, self.to_bytes(format)?).map_err(|x| x.to_string())
+    }
+    pub fn to_bytes(&self, format: MapFormat) -> Result<Vec<u8>, String> {
+        match format {
+            MapFormat::Json => serde_json::to_vec_pretty(self).map_err(|x| x.to_string()),
+            MapFormat::Ascii => ascii::export(self).map(String::into_bytes),
+            MapFormat::Binary => {
+                let mut data = BINARY_MAGIC.to_vec();
+                data.extend(BINARY_VERSION.to_le_bytes());
+                bincode::DefaultOptions::new()
+                    .serialize_into(&mut data, self)
+                    .map_err(|x| x.to_string())?;
+                Ok(data)
+            }
         }
     }
     pub fn into_level(self) -> Level {
         Level {
+            name: String::new(),
             grid: self.grid,
             player_start: [self.player_start[1], self.player_start[0]],
+            scrolling: self.scrolling,
         }
     }
 }
+impl Default for IOMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 pub struct MapRenderer {
     pub grid: Vec<Vec<RenderJobID>>,
     pub others: Vec<RenderJobID>,
     pub guide: Vec<RenderJobID>,
+    pub preview: Vec<RenderJobID>, // what a tool is about to paint
+    pub backdrop: RenderJobID,
     pub mouse_hover: RenderJobID,
+    pub highlight: RenderJobID, // over top of the selected object
+    pub minimap: Minimap,
+    pub hud: Hud,
+    pub view: View, // how the grid is zoomed and panned
 }
 impl MapRenderer {
     pub fn new(jobs: &mut RenderJobs) -> MapRenderer {
@@ -48,7 +184,25 @@ impl MapRenderer {
             grid: Vec::new(),
             others: Vec::new(),
             guide: Vec::new(),
+            preview: Vec::new(),
+            // the grid can be zoomed in past its room, so the rest of the window is covered up
+            backdrop: jobs.add_job(
+                Rect::new(
+                    BLACK,
+                    [
+                        MEDIT_TILES as f64 * MEDIT_TILE_SIZE,
+                        0.0,
+                        consts::MEDIT_EXTRA_ROOM as f64 * MEDIT_TILE_SIZE,
+                        MEDIT_TILES as f64 * MEDIT_TILE_SIZE,
+                    ],
+                ),
+                consts::UI_LAYER,
+            ),
             mouse_hover: jobs.add_job(RenderJob::default(), consts::UI_LAYER),
+            highlight: jobs.add_job(RenderJob::default(), consts::UI_LAYER),
+            minimap: Minimap::new(),
+            hud: Hud::new(jobs),
+            view: View::new(),
         };
         res.init(jobs);
         res
@@ -59,19 +213,18 @@ impl MapRenderer {
             let mut v: Vec<RenderJobID> = Vec::new();
             for j in 0..consts::MEDIT_TILES {
                 let mut job = RenderJob::default();
-                *job.bounds() = GridSpace::location(j, i, MEDIT_TILE_SIZE, [0.0; 2]);
+                *job.bounds() = self.view.to_pixels([j as f64, i as f64, 1.0, 1.0]);
                 v.push(jobs.add_job(job, CONTENT_LAYER));
             }
             self.grid.push(v);
         }
-        for i in 0..(GridSpace::MAX as u32) {
-            let mut job = GridSpace::from_id(i as usize).to_render_job();
-            *job.bounds() = GridSpace::location(
-                consts::MEDIT_TILES + i % MEDIT_GUIDE_SIZE,
-                i / MEDIT_GUIDE_SIZE,
-                MEDIT_TILE_SIZE,
-                [0.0; 2],
-            );
+        for (i, tile) in tiles().tiles.iter().enumerate() {
+            let Some([x, y]) = tile.palette else {
+                continue;
+            };
+            let mut job = GridSpace(i).to_render_job();
+            *job.bounds() =
+                GridSpace::location(consts::MEDIT_TILES + x, y, MEDIT_TILE_SIZE, [0.0; 2]);
             self.guide.push(jobs.add_job(job, consts::UI_LAYER));
         }
     }
@@ -81,7 +234,7 @@ impl MapRenderer {
         }
         for line in &mut self.grid {
             for tile in line {
-                GridSpace::None.alter_render_job(jobs.get_job_mut(*tile).unwrap());
+                GridSpace::empty().alter_render_job(jobs.get_job_mut(*tile).unwrap());
             }
         }
     }
@@ -95,18 +248,65 @@ impl MapRenderer {
         {
             tile.alter_render_job(jobs.get_job_mut(*id).unwrap());
         }
-        let others: Vec<RenderJobID> = to_load
+        self.others = to_load
             .others
             .iter()
             .map(|template| {
-                template.object.job.as_ref().map(|x| {
-                    let _new_x = x.clone();
-                    todo!()
-                })
+                // anything that can't be drawn still gets a blank job, so that the ids line up
+                let Some(mut object) = template.object.to_object(&self.view.transform()) else {
+                    return jobs.add_job(RenderJob::default(), CONTENT_LAYER);
+                };
+                if let RenderJobComponent::Text(val) = &mut object.render(jobs, 1.0).cmp {
+                    // text is sized for the game's tiles, which are bigger than the editor's
+                    val.size = (val.size as f64 * self.view.tile_size() / GRID_SIZE).round() as u32;
+                }
+                object.job_id.expect("Safe unwrap")
             })
-            .flatten()
             .collect();
-        self.others = others;
+    }
+    // shows where typing goes: the end of a text block's text
+    pub fn show_caret(&mut self, jobs: &mut RenderJobs, index: usize) {
+        let job = self.others.get(index).and_then(|id| jobs.get_job_mut(*id));
+        if let Some(RenderJobComponent::Text(val)) = job.map(|x| &mut x.cmp) {
+            val.text.to_mut().push('|');
+        }
+    }
+    // highlights an object ([x, y, width, height] in tiles), or nothing
+    pub fn highlight(&mut self, jobs: &mut RenderJobs, bounds: Option<[f64; 4]>) {
+        let job = match bounds {
+            Some(bounds) => Rect::new(FAINT_WHITE, self.view.to_pixels(bounds)),
+            None => RenderJob::default(),
+        };
+        jobs.set_job(job, self.highlight);
+    }
+    // shows the tiles ([row, column]) that are about to be painted, over top of the grid
+    pub fn show_preview(&mut self, jobs: &mut RenderJobs, tiles: &[[usize; 2]], item: GridSpace) {
+        for id in mem::take(&mut self.preview) {
+            jobs.remove_job(id);
+        }
+        for [row, column] in tiles {
+            let bounds = self.view.to_pixels([*column as f64, *row as f64, 1.0, 1.0]);
+            let job = if item == GridSpace::empty() {
+                // there's nothing to see when erasing, so the tiles are just highlighted
+                Rect::new(FAINT_WHITE, bounds)
+            } else {
+                let mut job = RenderJob::default();
+                *job.bounds() = bounds;
+                item.alter_render_job_mouse(&mut job);
+                job
+            };
+            self.preview.push(jobs.add_job(job, CONTENT_LAYER + 1));
+        }
+    }
+    // Moves the grid to where a new view puts it. Everything else on it needs to be redrawn after.
+    pub fn set_view(&mut self, jobs: &mut RenderJobs, view: View) {
+        self.view = view;
+        for (i, line) in self.grid.iter().enumerate() {
+            for (j, id) in line.iter().enumerate() {
+                *jobs.get_job_mut(*id).unwrap().bounds() =
+                    view.to_pixels([j as f64, i as f64, 1.0, 1.0]);
+            }
+        }
     }
     pub fn replace(&mut self, jobs: &mut RenderJobs, new_item: GridSpace, pos: [usize; 2]) {
         let id = self.grid[pos[0]][pos[1]];
@@ -117,10 +317,28 @@ pub struct Map {
     grid: Vec<Vec<LevelGrid>>,
     current: [usize; 2],
     size: [usize; 2],
+    scrolling: bool, // see Level::scrolling
     player_start: [usize; 4],
     file_path: PathBuf,
     renderer: MapRenderer,
     current_item: GridSpace,
+    format: MapFormat, // what the file is saved as
+    history: History,
+    tool: Tool,
+    anchor: Option<[usize; 2]>, // where a drag started ([row, column])
+    preview: Vec<[usize; 2]>,   // what the tool is about to paint ([row, column])
+    selection: Option<[[usize; 2]; 2]>, // corners ([row, column]) of what the select tool picked
+    clipboard: Option<Clipboard>,
+    playtest: Option<Playtest>, // the game being played on this level, if it's being tested
+    typing: Option<Typing>,     // the text block being typed into, if in typing mode
+    opening: Option<String>,    // the path of the level to open, while it's being typed in
+    selected_object: Option<([usize; 2], usize)>, // the object tool's pick: its screen ([x, y]) and index in others
+    drag: Option<Drag>,                           // the selected object, while it's being dragged
+    saved_state: Option<u64>, // what the level looked like when it was last saved (see History::state)
+    stashed_state: Option<u64>, // what the level looked like when it was last stashed for recovery
+    last_autosave: Instant,
+    recovery: Option<IOMap>, // unsaved work from last time, that can be restored
+    pan_from: Option<[f64; 2]>, // where the mouse was last frame, while panning with the middle button
 }
 /**
  * Keybinds -
@@ -128,212 +346,861 @@ pub struct Map {
  *  ctrl + del - remove the entire column where this grid is - DONE
  *  alt + del - remove the entire row where this grid is - DONE
  *  arrow - go that direction, shifting to the leftmost/rightmost/top/bottom grid of possible - DONE
+ *  left click (on the minimap) - go to that grid - DONE
  *  ctrl + arrow - creates a new row/column in that direction - DONE
- *  left click - replace the item on the grid with the current item selected.
+ *  left click - replace the item on the grid with the current item selected (with the pencil tool).
+ *  p - pencil tool: paint one tile at a time - DONE
+ *  r - rectangle tool: drag to fill a rectangle - DONE
+ *  o - outline tool: drag to draw the edges of a rectangle - DONE
+ *  l - line tool: drag to draw a straight line - DONE
+ *  f - fill tool: fill the connected area that's the same as the clicked tile - DONE
+ *  b - object tool: drag objects around, or click on an empty space to place the current item there as an object - DONE
+ *  shift + arrow - (object tool) make the selected object wider/narrower/taller/shorter - DONE
+ *  page up/page down - (object tool) move the selected object forwards/backwards a layer - DONE
+ *  tab - (object tool) switch the selected object's behavior - DONE
+ *  del - (object tool) delete the selected object, instead of clearing the grid - DONE
  *  right click - replace the current item selected with the item on the grid.
- *  enter - exit typing mode (typing mode is automatically entered when a complicated block [i.e. text] is placed) - TODO
- *  shift + enter - create a line break inside typing mode - TODO
+ *  e - text tool: click to place a text block, or click on one to change it. Either way, typing mode is entered - DONE
+ *  enter - exit typing mode (typing mode is automatically entered when a complicated block [i.e. text] is placed) - DONE
+ *  shift + enter - create a line break inside typing mode - DONE
+ *  del - (in typing mode) erase the last character. A text block left empty is removed - DONE
  *  ctrl + enter - save your work - DONE
+ *  ctrl + z - undo - DONE
+ *  ctrl + y - redo - DONE
+ *  s - select tool: drag to pick a rectangle of tiles - DONE
+ *  ctrl + c - copy the selected tiles - DONE
+ *  ctrl + x - cut the selected tiles - DONE
+ *  ctrl + v - paste, with the top left corner under the cursor - DONE
+ *  ctrl + o - open another level, keeping what's been copied. The path is typed in (enter to open, esc to cancel),
+ *      and the level has to be saved first - DONE
+ *  t - playtest this screen, starting under the cursor (or at the start if the cursor isn't on the grid).
+ *      Dying, reaching a goal or pressing t again goes back to editing. - DONE
+ *  ctrl + b - switch between saving as json, binary and ascii - DONE
+ *  ctrl + l - switch the level between flipping between screens and scrolling around them all - DONE
  *  alt + enter - save your work and quit - DONE
  *  ctrl + esc - quit without saving - DONE
+ *  ctrl + r - restore unsaved work from last time (offered when a level with some is opened) - DONE
+ *  scroll wheel - zoom the grid in/out, around the cursor - DONE
+ *  middle click + drag - pan around the grid while zoomed in - DONE
+ * Unsaved work is autosaved to [level path].recovery, which is also written if the editor crashes.
  */
 impl Map {
     pub fn new(path: PathBuf, jobs: &mut RenderJobs) -> Map {
         let mut grid = LevelGrid::new();
-        grid.contents[0][0] = GridSpace::StartingLocation;
+        grid.contents[0][0] = GridSpace::start();
         Map {
             grid: vec![vec![grid]],
             player_start: [0; 4],
             size: [1, 1],
+            scrolling: false,
             current: [0, 0],
             file_path: path,
             renderer: MapRenderer::new(jobs),
-            current_item: GridSpace::None,
+            current_item: GridSpace::empty(),
+            format: MapFormat::Json,
+            history: History::new(),
+            tool: Tool::Pencil,
+            anchor: None,
+            preview: Vec::new(),
+            selection: None,
+            clipboard: None,
+            playtest: None,
+            typing: None,
+            opening: None,
+            selected_object: None,
+            drag: None,
+            saved_state: None,
+            stashed_state: Some(0), // there's nothing worth recovering until something changes
+            last_autosave: Instant::now(),
+            recovery: None,
+            pan_from: None,
         }
     }
     pub fn tick(&mut self, jobs: &mut RenderJobs, input: &mut InputVars) -> bool {
-        let mouse_pos = MousePos::get(input.mouse_pos);
+        // text is only wanted in typing mode, and is thrown away otherwise
+        let typed = input.grab_text();
+        if let Some(playtest) = &mut self.playtest {
+            if playtest.tick(jobs, input) {
+                self.playtest.take().expect("Safe unwrap").end(jobs);
+            }
+            return false;
+        }
+        self.move_view(jobs, input);
+        let mouse_pos = MousePos::get(input.mouse_pos, &self.renderer.view);
+        if self.typing.is_some() {
+            self.type_text(jobs, input, &typed);
+            self.show_status(jobs, &mouse_pos);
+            return false;
+        }
+        if self.opening.is_some() {
+            self.type_path(jobs, input, &typed);
+            self.show_status(jobs, &mouse_pos);
+            return false;
+        }
 
         if input.key_down(Key::LCtrl as u32) || input.key_down(Key::RCtrl as u32) {
             // ctrl + [key]
             if input.key_pressed(Key::Escape as u32) {
-                self.info();
+                recovery::discard(&self.file_path);
                 return true;
             } else if input.key_pressed(Key::Return as u32) {
-                if let Err(e) = self.save() {
-                    println!("Saving failed: {}", e);
-                } else {
-                    println!("Saved!");
+                match self.save() {
+                    Ok(()) => self.message("Saved!"),
+                    Err(e) => self.message(format!("Saving failed: {}", e)),
                 }
+            } else if input.key_pressed(Key::B as u32) {
+                self.format = match self.format {
+                    MapFormat::Json => MapFormat::Binary,
+                    MapFormat::Binary => MapFormat::Ascii,
+                    MapFormat::Ascii => MapFormat::Json,
+                };
+                self.message(format!("Saving as {:?}", self.format));
+            } else if input.key_pressed(Key::L as u32) {
+                self.edit_level(jobs, |map, _| map.scrolling = !map.scrolling);
+                let message = match self.scrolling {
+                    true => "The level scrolls",
+                    false => "The level flips between screens",
+                };
+                self.message(message);
+            } else if input.key_pressed(Key::C as u32) {
+                self.copy();
+            } else if input.key_pressed(Key::X as u32) {
+                self.cut(jobs);
+            } else if input.key_pressed(Key::V as u32) {
+                self.paste(jobs, &mouse_pos);
+            } else if input.key_pressed(Key::O as u32) {
+                self.start_opening();
+            } else if input.key_pressed(Key::R as u32) {
+                self.restore(jobs);
+            } else if input.key_pressed(Key::Z as u32) {
+                self.undo(jobs);
+            } else if input.key_pressed(Key::Y as u32) {
+                self.redo(jobs);
             } else if input.key_pressed(Key::Up as u32) {
-                self.grow_vert();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.edit_level(jobs, |map, _| map.grow_vert());
             } else if input.key_pressed(Key::Down as u32) {
-                self.grow_vert_plus();
-                self.down();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.edit_level(jobs, |map, _| {
+                    map.grow_vert_plus();
+                    map.down();
+                });
             } else if input.key_pressed(Key::Left as u32) {
-                self.grow_horizon();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.edit_level(jobs, |map, _| map.grow_horizon());
             } else if input.key_pressed(Key::Right as u32) {
-                self.grow_horizon_plus();
-                self.right();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.edit_level(jobs, |map, _| {
+                    map.grow_horizon_plus();
+                    map.right();
+                });
             } else if input.key_pressed(Key::Backspace as u32) {
-                self.shrink_horizon(jobs);
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.edit_level(jobs, Map::shrink_horizon);
             }
         } else if input.key_down(Key::RAlt as u32) || input.key_down(Key::LAlt as u32) {
             // alt + [key]
             if input.key_pressed(Key::Return as u32) {
-                if let Err(e) = self.save() {
-                    println!("Saving failed: {}", e);
-                } else {
-                    return true;
+                match self.save() {
+                    Ok(()) => return true,
+                    Err(e) => self.message(format!("Saving failed: {}", e)),
                 }
-                self.info();
             } else if input.key_pressed(Key::Backspace as u32) {
-                self.shrink_vert(jobs);
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.edit_level(jobs, Map::shrink_vert);
             }
-        } else if input.key_down(Key::LShift as u32) || input.key_down(Key::RShift as u32) {
-            // shift + [key]
-            if input.key_pressed(Key::Return as u32) {
-                // TODO: inserts /n
-                todo!();
+        } else if (input.key_down(Key::LShift as u32) || input.key_down(Key::RShift as u32))
+            && self.tool == Tool::Object
+        {
+            // shift + [key] (object tool)
+            let resize = [
+                (Key::Right, [1.0, 0.0]),
+                (Key::Left, [-1.0, 0.0]),
+                (Key::Down, [0.0, 1.0]),
+                (Key::Up, [0.0, -1.0]),
+            ]
+            .into_iter()
+            .find_map(|(key, steps)| input.key_pressed(key as u32).then_some(steps));
+            if let Some(steps) = resize {
+                self.edit_object(jobs, |x| objects::resize(x, steps));
             }
         } else {
             // [key]
-            if input.key_pressed(Key::Backspace as u32) {
-                self.grid[self.current[1]][self.current[0]] = LevelGrid::new();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
-            } else if input.key_pressed(Key::Return as u32) {
-                // exits typing mode
-                todo!()
+            let object_tool = self.tool == Tool::Object;
+            if object_tool && input.key_pressed(Key::Backspace as u32) {
+                self.delete_object(jobs);
+            } else if object_tool && input.key_pressed(Key::PageUp as u32) {
+                self.edit_object(jobs, |x| objects::change_layer(x, true));
+            } else if object_tool && input.key_pressed(Key::PageDown as u32) {
+                self.edit_object(jobs, |x| objects::change_layer(x, false));
+            } else if object_tool && input.key_pressed(Key::Tab as u32) {
+                self.edit_object(jobs, objects::next_behavior);
+            } else if input.key_pressed(Key::Backspace as u32) {
+                self.edit_level(jobs, |map, _| {
+                    map.grid[map.current[1]][map.current[0]] = LevelGrid::new();
+                });
             } else if input.key_pressed(Key::Up as u32) {
                 self.up();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.refresh(jobs);
             } else if input.key_pressed(Key::Down as u32) {
                 self.down();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.refresh(jobs);
             } else if input.key_pressed(Key::Left as u32) {
                 self.left();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.refresh(jobs);
             } else if input.key_pressed(Key::Right as u32) {
                 self.right();
-                self.renderer
-                    .load(jobs, &self.grid[self.current[1]][self.current[0]]);
-                self.info();
+                self.refresh(jobs);
+            } else if let Some(tool) = [
+                (Key::P, Tool::Pencil),
+                (Key::R, Tool::Rect),
+                (Key::O, Tool::Outline),
+                (Key::L, Tool::Line),
+                (Key::F, Tool::Fill),
+                (Key::S, Tool::Select),
+                (Key::E, Tool::Text),
+                (Key::B, Tool::Object),
+            ]
+            .into_iter()
+            .find_map(|(key, tool)| input.key_pressed(key as u32).then_some(tool))
+            {
+                self.tool = tool;
+                self.anchor = None;
+                self.message(format!("Tool: {:?}", tool));
+            } else if input.key_pressed(Key::T as u32) {
+                self.playtest(jobs, &mouse_pos);
+                return false;
             }
         }
+        if !input.mouse_down(LEFT_MOUSE) {
+            // a drag is over, so everything painted during it is undone together
+            self.history.end_group();
+            self.drop_object();
+        }
+        self.use_tool(jobs, input, &mouse_pos);
         if input.mouse_down(LEFT_MOUSE) {
-            if let Some(position) = mouse_pos.grid_location {
-                if let GridSpace::StartingLocation = self.current_item {
-                    self.grid[self.player_start[1]][self.player_start[0]].contents
-                        [self.player_start[2]][self.player_start[3]] = GridSpace::None;
-                    if self.player_start[..2] == self.current {
-                        self.renderer.replace(
-                            jobs,
-                            GridSpace::None,
-                            [self.player_start[2], self.player_start[3]],
-                        );
+            if let (Some(position), Tool::Pencil) = (mouse_pos.grid_location, self.tool()) {
+                let spot = [self.current[0], self.current[1], position[1], position[0]];
+                if self.current_item == GridSpace::start() {
+                    if spot != self.player_start {
+                        let old_start = self.player_start;
+                        self.set_tile(jobs, old_start, GridSpace::empty());
+                        self.set_tile(jobs, spot, GridSpace::start());
+                        self.player_start = spot;
+                        self.history.start(old_start, spot);
                     }
-                    self.grid[self.current[1]][self.current[0]].contents[position[1]]
-                        [position[0]] = GridSpace::StartingLocation;
-                    self.player_start =
-                        [self.current[0], self.current[1], position[1], position[0]];
-                    self.renderer.replace(
-                        jobs,
-                        self.current_item.clone(),
-                        [position[1], position[0]],
-                    );
                 } else {
-                    self.grid[self.current[1]][self.current[0]].contents[position[1]]
-                        [position[0]] = self.current_item.clone();
-                    self.renderer.replace(
-                        jobs,
-                        self.current_item.clone(),
-                        [position[1], position[0]],
-                    );
+                    self.set_tile(jobs, spot, self.current_item);
+                }
+            } else if let (Some(pos), Tool::Text) = (mouse_pos.exact, self.tool()) {
+                if input.mouse_pressed(LEFT_MOUSE) {
+                    self.start_typing(jobs, pos);
+                }
+            } else if let (Some(pos), Tool::Object) = (mouse_pos.exact, self.tool()) {
+                if input.mouse_pressed(LEFT_MOUSE) {
+                    self.grab_object(jobs, pos);
+                } else {
+                    self.drag_object(jobs, pos);
+                }
+            } else if let Some(screen) = minimap::screen_at(input.mouse_pos, self.size) {
+                if screen != self.current {
+                    self.current = screen;
+                    self.refresh(jobs);
                 }
             } else if let Some(position) = mouse_pos.guide_location {
-                let item_index = position[1] * (MEDIT_GUIDE_SIZE as usize) + position[0];
-                self.current_item = GridSpace::from_id(item_index);
-                self.current_item.alter_render_job_mouse(
-                    &mut jobs.get_job_mut(self.renderer.mouse_hover).unwrap(),
-                );
+                if let Some(item) = tiles().at_palette(position) {
+                    self.current_item = item;
+                    self.current_item.alter_render_job_mouse(
+                        jobs.get_job_mut(self.renderer.mouse_hover).unwrap(),
+                    );
+                }
             }
         } else if input.mouse_down(RIGHT_MOUSE) {
             if let Some(position) = mouse_pos.grid_location {
-                self.current_item = self.grid[self.current[1]][self.current[0]].contents
-                    [position[1]][position[0]]
-                    .clone();
-                self.current_item.alter_render_job_mouse(
-                    &mut jobs.get_job_mut(self.renderer.mouse_hover).unwrap(),
-                );
+                self.current_item =
+                    self.grid[self.current[1]][self.current[0]].contents[position[1]][position[0]];
+                self.current_item
+                    .alter_render_job_mouse(jobs.get_job_mut(self.renderer.mouse_hover).unwrap());
             } else if let Some(position) = mouse_pos.guide_location {
-                let item_index = position[1] * (MEDIT_GUIDE_SIZE as usize) + position[0];
-                self.current_item = GridSpace::from_id(item_index);
-                self.current_item.alter_render_job_mouse(
-                    &mut jobs.get_job_mut(self.renderer.mouse_hover).unwrap(),
-                );
+                if let Some(item) = tiles().at_palette(position) {
+                    self.current_item = item;
+                    self.current_item.alter_render_job_mouse(
+                        jobs.get_job_mut(self.renderer.mouse_hover).unwrap(),
+                    );
+                }
+            }
+        }
+        self.autosave();
+        self.show_status(jobs, &mouse_pos);
+        false
+    }
+    // Keeps unsaved work ready to be recovered, and writes it out every so often.
+    fn autosave(&mut self) {
+        let state = self.history.state();
+        if state.is_some() && state != self.stashed_state {
+            self.stashed_state = state;
+            if state == self.saved_state {
+                // back to how it was saved, so there's nothing to recover
+                recovery::discard(&self.file_path);
+            } else {
+                recovery::stash(&self.file_path, self.to_io_map());
+            }
+        }
+        if self.last_autosave.elapsed() > MEDIT_AUTOSAVE_TIME {
+            self.last_autosave = Instant::now();
+            if let Err(e) = recovery::flush() {
+                self.message(format!("Autosaving failed: {}", e));
             }
         }
-        return false;
     }
-    pub fn info(&self) {
-        println!("Grid size: [{}, {}]", self.size[0], self.size[1]);
-        println!("Player start: {:?}", self.player_start);
-        println!("Current position: {:?}", self.current);
+    // Replaces the level with the unsaved work found when it was opened. It can be undone.
+    pub fn restore(&mut self, jobs: &mut RenderJobs) {
+        let Some(io_map) = self.recovery.take() else {
+            self.message("There's nothing to restore!");
+            return;
+        };
+        self.edit_level(jobs, |map, _| {
+            map.grid = io_map.grid;
+            map.size = io_map.size;
+            map.scrolling = io_map.scrolling;
+            map.player_start = io_map.player_start;
+            map.current = [0, 0];
+        });
+        self.message("Restored unsaved work");
+    }
+    // Zooms with the scroll wheel and pans with the middle mouse button, when the cursor is over the grid.
+    fn move_view(&mut self, jobs: &mut RenderJobs, input: &InputVars) {
+        let mut view = self.renderer.view;
+        let pos = input.mouse_pos;
+        if input.mouse_pressed(MIDDLE_MOUSE) && MousePos::on_grid(pos) {
+            self.pan_from = Some(pos);
+        } else if !input.mouse_down(MIDDLE_MOUSE) {
+            self.pan_from = None;
+        }
+        if let Some(from) = &mut self.pan_from {
+            view.pan([pos[0] - from[0], pos[1] - from[1]]);
+            *from = pos;
+        }
+        if input.scroll[1] != 0.0 && MousePos::on_grid(pos) {
+            view.zoom_at(pos, input.scroll[1]);
+        }
+        if view != self.renderer.view {
+            self.renderer.set_view(jobs, view);
+            self.refresh(jobs);
+            let item = self.preview_item();
+            self.renderer.show_preview(jobs, &self.preview, item);
+        }
+    }
+    // brings the minimap and status bar up to date
+    fn show_status(&mut self, jobs: &mut RenderJobs, mouse_pos: &MousePos) {
+        let start = [self.player_start[0], self.player_start[1]];
+        self.renderer
+            .minimap
+            .update(jobs, &self.grid, self.current, start);
+        let status = self.status(mouse_pos);
+        self.renderer.hud.update(jobs, &status);
+    }
+    // what the status bar says, besides messages
+    fn status(&self, mouse_pos: &MousePos) -> Vec<String> {
+        let saved = match self.history.state() == self.saved_state {
+            true => "Saved",
+            false => "Unsaved changes",
+        };
+        let file = match &self.opening {
+            Some(path) => format!("Open: {}_", path),
+            None => self.file_path.display().to_string(),
+        };
+        let mut res = vec![
+            file,
+            format!("{} ({:?})", saved, self.format),
+            format!(
+                "Screen [{}, {}] of [{}, {}]",
+                self.current[0], self.current[1], self.size[0], self.size[1]
+            ),
+            format!("Start: {:?}", self.player_start),
+            format!("Scrolling: {}", self.scrolling),
+            format!("Tool: {:?}", self.tool),
+            format!("Item: {}", self.current_item.def().name),
+            format!("Zoom: {:.2}x", self.renderer.view.zoom),
+        ];
+        let screen = &self.grid[self.current[1]][self.current[0]];
+        if let Some([column, row]) = mouse_pos.grid_location {
+            let tile = screen.contents[row][column];
+            res.push(format!("[{}, {}]: {}", column, row, tile.def().name));
+        } else if let Some(tile) = mouse_pos.guide_location.and_then(|x| tiles().at_palette(x)) {
+            res.push(format!("Guide: {}", tile.def().name));
+        }
+        if let Some(index) = self.selected_object() {
+            res.push(objects::describe(&screen.others[index]));
+        }
+        res
+    }
+    // the start can only go in one place, so it's always placed with the pencil
+    fn tool(&self) -> Tool {
+        if self.current_item == GridSpace::start()
+            && !matches!(self.tool, Tool::Select | Tool::Text)
+        {
+            Tool::Pencil
+        } else {
+            self.tool
+        }
+    }
+    // what the preview is drawn with. The selection is highlighted rather than previewed.
+    fn preview_item(&self) -> GridSpace {
+        match self.tool() {
+            Tool::Select => GridSpace::empty(),
+            _ => self.current_item,
+        }
+    }
+    // Previews what the shape tools are about to paint, and paints it once they're done.
+    fn use_tool(&mut self, jobs: &mut RenderJobs, input: &InputVars, mouse_pos: &MousePos) {
+        let tool = self.tool();
+        let cursor = mouse_pos.grid_location.map(|x| [x[1], x[0]]);
+        if tool.drags() && input.mouse_pressed(LEFT_MOUSE) && cursor.is_some() {
+            self.anchor = cursor;
+        }
+        if let (Tool::Select, Some(anchor), Some(cursor)) = (tool, self.anchor, cursor) {
+            self.selection = Some([anchor, cursor]);
+        }
+        let contents = &self.grid[self.current[1]][self.current[0]].contents;
+        let preview = match (cursor, self.anchor) {
+            (Some(cursor), _) if tool == Tool::Fill => tool.shape(cursor, cursor, contents),
+            (Some(cursor), Some(anchor)) if tool.drags() => tool.shape(anchor, cursor, contents),
+            // dragging off of the grid keeps the last shape
+            (None, Some(_)) if tool.drags() => self.preview.clone(),
+            (_, None) if tool == Tool::Select => match self.selection {
+                Some([a, b]) => tool.shape(a, b, contents),
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        if preview != self.preview {
+            let item = self.preview_item();
+            self.renderer.show_preview(jobs, &preview, item);
+            self.preview = preview;
+        }
+        let done = if tool.drags() {
+            input.mouse_released(LEFT_MOUSE) && self.anchor.take().is_some()
+        } else {
+            tool == Tool::Fill && input.mouse_pressed(LEFT_MOUSE)
+        };
+        if done && tool != Tool::Select {
+            for [row, column] in mem::take(&mut self.preview) {
+                let spot = [self.current[0], self.current[1], row, column];
+                self.set_tile(jobs, spot, self.current_item);
+            }
+            self.renderer.show_preview(jobs, &[], self.current_item);
+            // the whole shape is undone at once
+            self.history.end_group();
+        }
+    }
+    pub fn copy(&mut self) {
+        match self.selection {
+            Some(corners) => {
+                let contents = &self.grid[self.current[1]][self.current[0]].contents;
+                self.clipboard = Some(Clipboard::copy(contents, corners));
+                self.message("Copied!");
+            }
+            None => self.message("Nothing is selected!"),
+        }
+    }
+    // copies the selected tiles, then erases them (except for the start)
+    pub fn cut(&mut self, jobs: &mut RenderJobs) {
+        self.copy();
+        let Some(corners) = self.selection else {
+            return;
+        };
+        let [[top, left], [bottom, right]] = clipboard::bounds(corners);
+        for row in top..=bottom {
+            for column in left..=right {
+                let spot = [self.current[0], self.current[1], row, column];
+                if spot != self.player_start {
+                    self.set_tile(jobs, spot, GridSpace::empty());
+                }
+            }
+        }
+        self.history.end_group();
+    }
+    // Pastes with the top left corner under the cursor. Whatever goes past the edge of the screen
+    // is left out, and so is the start (there can only be one).
+    pub fn paste(&mut self, jobs: &mut RenderJobs, mouse_pos: &MousePos) {
+        let Some(position) = mouse_pos.grid_location else {
+            self.message("There's nowhere to paste it! Put the cursor on the grid");
+            return;
+        };
+        // it's taken while pasting, and put back after
+        let Some(clipboard) = self.clipboard.take() else {
+            self.message("Nothing to paste!");
+            return;
+        };
+        for ([row, column], tile) in clipboard.paste([position[1], position[0]]) {
+            let spot = [self.current[0], self.current[1], row, column];
+            let fits = self.grid[self.current[1]][self.current[0]]
+                .contents
+                .get(row)
+                .is_some_and(|x| column < x.len());
+            if fits && tile != GridSpace::start() && spot != self.player_start {
+                self.set_tile(jobs, spot, tile);
+            }
+        }
+        self.history.end_group();
+        self.clipboard = Some(clipboard);
+    }
+    // changes a tile, keeping track of it for undoing
+    fn set_tile(&mut self, jobs: &mut RenderJobs, spot: [usize; 4], new: GridSpace) {
+        let tile = &mut self.grid[spot[1]][spot[0]].contents[spot[2]][spot[3]];
+        self.history.tile(TileEdit {
+            spot,
+            old: *tile,
+            new,
+        });
+        *tile = new;
+        if spot[..2] == self.current {
+            self.renderer.replace(jobs, new, [spot[2], spot[3]]);
+        }
+    }
+    // makes a change that adds, removes or clears screens, keeping track of it for undoing
+    fn edit_level(&mut self, jobs: &mut RenderJobs, edit: impl FnOnce(&mut Map, &mut RenderJobs)) {
+        let before = self.snapshot();
+        edit(self, jobs);
+        self.record(before);
+        self.refresh(jobs);
+    }
+    // keeps track of a change to the level for undoing, unless nothing changed
+    fn record(&mut self, before: Snapshot) {
+        if let Some(diff) = LevelDiff::new(before, self.snapshot()) {
+            self.history.push(Change::Level(Box::new(diff)));
+        }
+    }
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            grid: self.grid.clone(),
+            size: self.size,
+            player_start: self.player_start,
+            current: self.current,
+            scrolling: self.scrolling,
+        }
+    }
+    pub fn undo(&mut self, jobs: &mut RenderJobs) {
+        match self.history.undo() {
+            Some(change) => self.apply(jobs, change, true),
+            None => self.message("Nothing to undo!"),
+        }
+    }
+    pub fn redo(&mut self, jobs: &mut RenderJobs) {
+        match self.history.redo() {
+            Some(change) => self.apply(jobs, change, false),
+            None => self.message("Nothing to redo!"),
+        }
+    }
+    // Undoes or redoes a change, and shows the screen it happened on. The selected object might not
+    // be there anymore, so it's let go of.
+    fn apply(&mut self, jobs: &mut RenderJobs, change: Change, undo: bool) {
+        self.selected_object = None;
+        self.drag = None;
+        match change {
+            Change::Tiles { tiles, start } => {
+                let mut apply = |edit: &TileEdit| {
+                    let tile = if undo { edit.old } else { edit.new };
+                    self.grid[edit.spot[1]][edit.spot[0]].contents[edit.spot[2]][edit.spot[3]] =
+                        tile;
+                    self.current = [edit.spot[0], edit.spot[1]];
+                };
+                if undo {
+                    tiles.iter().rev().for_each(&mut apply);
+                } else {
+                    tiles.iter().for_each(&mut apply);
+                }
+                if let Some([old, new]) = start {
+                    self.player_start = if undo { old } else { new };
+                }
+            }
+            Change::Level(diff) => {
+                let level = Snapshot {
+                    grid: mem::take(&mut self.grid),
+                    size: self.size,
+                    player_start: self.player_start,
+                    current: self.current,
+                    scrolling: self.scrolling,
+                };
+                let snapshot = diff.apply(level, undo);
+                self.grid = snapshot.grid;
+                self.size = snapshot.size;
+                self.player_start = snapshot.player_start;
+                self.current = snapshot.current;
+                self.scrolling = snapshot.scrolling;
+            }
+        }
+        self.refresh(jobs);
+    }
+    // the selected object, if it's on the current screen
+    fn selected_object(&self) -> Option<usize> {
+        let (screen, index) = self.selected_object?;
+        let others = &self.grid[self.current[1]][self.current[0]].others;
+        (screen == self.current && index < others.len()).then_some(index)
+    }
+    // Picks up the object under pos (in tiles) to drag it around. If there isn't one, the current
+    // item is placed there as a new object instead.
+    fn grab_object(&mut self, jobs: &mut RenderJobs, pos: [f64; 2]) {
+        self.history.end_group();
+        let before = self.snapshot();
+        let others = &mut self.grid[self.current[1]][self.current[0]].others;
+        let (index, placed) = match objects::at(others, pos) {
+            Some(val) => (val, false),
+            None => {
+                let corner = pos.map(objects::snap);
+                let Some(object) = objects::from_tile(self.current_item, corner) else {
+                    // clicking on nothing with nothing to place just lets go of the selection
+                    self.selected_object = None;
+                    self.refresh(jobs);
+                    return;
+                };
+                others.push(object);
+                (others.len() - 1, true)
+            }
+        };
+        let [x, y, ..] = objects::bounds(&others[index]).unwrap_or_default();
+        self.selected_object = Some((self.current, index));
+        self.drag = Some(Drag {
+            grab: [pos[0] - x, pos[1] - y],
+            before,
+            changed: placed,
+        });
+        self.refresh(jobs);
+    }
+    // moves the object that's being dragged, keeping the same spot of it under the cursor
+    fn drag_object(&mut self, jobs: &mut RenderJobs, pos: [f64; 2]) {
+        let (Some(index), Some(drag)) = (self.selected_object(), &mut self.drag) else {
+            return;
+        };
+        let object = &mut self.grid[self.current[1]][self.current[0]].others[index].object;
+        let [x, y] = [pos[0] - drag.grab[0], pos[1] - drag.grab[1]].map(objects::snap);
+        if object.x_pos != Some(x) || object.y_pos != Some(y) {
+            object.x_pos = Some(x);
+            object.y_pos = Some(y);
+            drag.changed = true;
+            self.refresh(jobs);
+        }
+    }
+    // lets go of the object that's being dragged, keeping track of the move for undoing
+    fn drop_object(&mut self) {
+        if let Some(drag) = self.drag.take() {
+            if drag.changed {
+                self.record(drag.before);
+            }
+        }
+    }
+    // changes the selected object, keeping track of it for undoing
+    fn edit_object(&mut self, jobs: &mut RenderJobs, edit: impl FnOnce(&mut BlockTemplate)) {
+        let Some(index) = self.selected_object() else {
+            self.message("No object is selected!");
+            return;
+        };
+        self.drop_object();
+        let before = self.snapshot();
+        let object = &mut self.grid[self.current[1]][self.current[0]].others[index];
+        edit(object);
+        self.record(before);
+        self.refresh(jobs);
+    }
+    pub fn delete_object(&mut self, jobs: &mut RenderJobs) {
+        let Some(index) = self.selected_object() else {
+            self.message("No object is selected!");
+            return;
+        };
+        self.drop_object();
+        let before = self.snapshot();
+        self.grid[self.current[1]][self.current[0]]
+            .others
+            .remove(index);
+        self.selected_object = None;
+        self.record(before);
+        self.refresh(jobs);
+    }
+    // Enters typing mode, on the text block under pos (in tiles), or on a new one placed there.
+    fn start_typing(&mut self, jobs: &mut RenderJobs, pos: [f64; 2]) {
+        self.history.end_group();
+        let before = self.snapshot();
+        let others = &mut self.grid[self.current[1]][self.current[0]].others;
+        let index = text::at(others, pos).unwrap_or_else(|| {
+            others.push(text::new_block(pos));
+            others.len() - 1
+        });
+        self.typing = Some(Typing { index, before });
+        self.refresh(jobs);
+        self.message("Typing! Enter to finish, shift + enter for a new line");
+    }
+    // typing mode: everything typed goes into the text block, until enter is pressed
+    fn type_text(&mut self, jobs: &mut RenderJobs, input: &InputVars, typed: &str) {
+        let Some(typing) = &self.typing else {
+            return;
+        };
+        let shift = input.key_down(Key::LShift as u32) || input.key_down(Key::RShift as u32);
+        if input.key_pressed(Key::Return as u32) && !shift {
+            self.stop_typing(jobs);
+            return;
+        }
+        let block = &mut self.grid[self.current[1]][self.current[0]].others[typing.index];
+        let Some(renderer) = text::text_mut(block) else {
+            return;
+        };
+        let text = renderer.text.to_mut();
+        let before = text.clone();
+        // enter and backspace come through as text too, but they're handled as keys
+        text.extend(typed.chars().filter(|x| !x.is_control()));
+        if input.key_pressed(Key::Return as u32) {
+            text.push('\n');
+        }
+        if input.key_pressed(Key::Backspace as u32) {
+            text.pop();
+        }
+        if *text != before {
+            text::fit(block);
+            self.refresh(jobs);
+        }
+    }
+    // leaves typing mode, keeping track of the text for undoing. Empty text blocks are removed.
+    fn stop_typing(&mut self, jobs: &mut RenderJobs) {
+        let Some(typing) = self.typing.take() else {
+            return;
+        };
+        let [x, y] = self.current;
+        let others = &mut self.grid[y][x].others;
+        let new = text::text(&others[typing.index]).unwrap_or_default();
+        let new = (!new.is_empty()).then(|| new.to_string());
+        if new.is_none() {
+            others.remove(typing.index);
+        }
+        let old = typing.before.grid[y][x].others.get(typing.index);
+        if old.and_then(text::text) != new.as_deref() {
+            self.record(typing.before);
+        }
+        self.refresh(jobs);
+    }
+    // redraws the screen, with a caret after the text that's being typed and the selected object highlighted
+    fn refresh(&mut self, jobs: &mut RenderJobs) {
+        let screen = &self.grid[self.current[1]][self.current[0]];
+        self.renderer.load(jobs, screen);
+        if let Some(typing) = &self.typing {
+            self.renderer.show_caret(jobs, typing.index);
+        }
+        let selected = self
+            .selected_object()
+            .and_then(|x| objects::bounds(&screen.others[x]));
+        self.renderer.highlight(jobs, selected);
+    }
+    // Starts playing the level as it is right now, without saving it.
+    pub fn playtest(&mut self, jobs: &mut RenderJobs, mouse_pos: &MousePos) {
+        self.history.end_group();
+        let io_map = self.playtest_map(mouse_pos);
+        self.playtest = Some(Playtest::new(io_map.into_level(), jobs));
+    }
+    // The level to playtest, which starts on this screen. The player starts wherever the starting
+    // location is, so it's moved under the cursor (or to the same spot on this screen, if the
+    // cursor isn't on the grid).
+    fn playtest_map(&self, mouse_pos: &MousePos) -> IOMap {
+        let mut io_map = self.to_io_map();
+        let [x, y, row, column] = io_map.player_start;
+        let position = match mouse_pos.grid_location {
+            Some(position) => [position[1], position[0]],
+            None => [row, column],
+        };
+        io_map.grid[y][x].contents[row][column] = GridSpace::empty();
+        let [x, y] = self.current;
+        io_map.grid[y][x].contents[position[0]][position[1]] = GridSpace::start();
+        io_map.player_start = [x, y, position[0], position[1]];
+        io_map
+    }
+    // whether the level is being played instead of edited
+    pub fn playtesting(&self) -> bool {
+        self.playtest.is_some()
+    }
+    // shows a message in the status bar for a few seconds
+    pub fn message(&mut self, text: impl Into<String>) {
+        self.renderer.hud.message(text);
     }
     // Loads the file into a path buffer. Returns true if successfully loaded.
     pub fn load(path: PathBuf, jobs: &mut RenderJobs) -> Result<Map, String> {
-        let io_map: IOMap = serde_json::from_slice(&fs::read(&path).map_err(|x| x.to_string())?)
-            .map_err(|x| x.to_string())?;
-        let mut map = Map {
-            grid: io_map.grid,
-            current: [0, 0],
-            size: io_map.size,
-            player_start: io_map.player_start,
-            file_path: path,
-            renderer: MapRenderer::new(jobs),
-            current_item: GridSpace::None,
-        };
-        map.renderer
-            .load(jobs, &map.grid[map.current[1]][map.current[0]]);
+        let mut map = Map::new(path.clone(), jobs);
+        map.open(path, jobs)?;
         Ok(map)
     }
+    // Starts typing in the path of another level to open, if there aren't any unsaved changes.
+    fn start_opening(&mut self) {
+        self.history.end_group();
+        if self.history.state() != self.saved_state {
+            self.message(
+                "There are unsaved changes! Save them (ctrl + enter) before opening another level",
+            );
+            return;
+        }
+        self.opening = Some(String::new());
+        self.message("Type the path of the level to open. Enter to open it, esc to cancel");
+    }
+    // everything typed goes into the path, until enter or esc is pressed
+    fn type_path(&mut self, jobs: &mut RenderJobs, input: &InputVars, typed: &str) {
+        let Some(path) = &mut self.opening else {
+            return;
+        };
+        if input.key_pressed(Key::Escape as u32) {
+            self.opening = None;
+            self.message("Opening cancelled");
+            return;
+        }
+        if input.key_pressed(Key::Return as u32) {
+            let path = self.opening.take().unwrap_or_default();
+            match self.open(PathBuf::from(path.trim()), jobs) {
+                Ok(()) => self.message(format!("Opened {}", path.trim())),
+                Err(e) => self.message(format!("Opening failed: {}", e)),
+            }
+            return;
+        }
+        path.extend(typed.chars().filter(|x| !x.is_control()));
+        if input.key_pressed(Key::Backspace as u32) {
+            path.pop();
+        }
+    }
+    // Looks for unsaved work on the level from last time, which ctrl + r restores. It's there even
+    // if the level itself was never saved, or can't be loaded.
+    pub fn check_recovery(&mut self) {
+        self.recovery = recovery::load_newer(&self.file_path);
+        if self.recovery.is_some() {
+            self.message("There's newer unsaved work from last time. Press ctrl + r to restore it");
+        }
+    }
+    // Switches to editing another level. The clipboard is kept, so things can be copied between levels.
+    pub fn open(&mut self, path: PathBuf, jobs: &mut RenderJobs) -> Result<(), String> {
+        let (io_map, format) = IOMap::load(&path)?;
+        // whatever wasn't saved in the level that was open is kept for recovering
+        if let Err(e) = recovery::flush() {
+            self.message(format!("Writing the recovery file failed: {}", e));
+        }
+        self.grid = io_map.grid;
+        self.current = [0, 0];
+        self.size = io_map.size;
+        self.scrolling = io_map.scrolling;
+        self.player_start = io_map.player_start;
+        self.file_path = path;
+        self.check_recovery();
+        self.format = format;
+        self.history = History::new();
+        self.saved_state = self.history.state();
+        self.stashed_state = self.saved_state;
+        self.anchor = None;
+        self.selection = None;
+        self.refresh(jobs);
+        Ok(())
+    }
     // Attempts to save the file. Returns true if successfully saved.
     pub fn save(&mut self) -> Result<(), String> {
-        let io_map = self.to_io_map();
-        let data = serde_json::to_string_pretty(&io_map).map_err(|x| x.to_string())?;
-        std::fs::write(self.file_path.clone(), data).map_err(|x| x.to_string())
+        self.history.end_group();
+        self.to_io_map().save(&self.file_path, self.format)?;
+        self.saved_state = self.history.state();
+        self.stashed_state = self.saved_state;
+        recovery::discard(&self.file_path);
+        Ok(())
     }
     pub fn to_io_map(&self) -> IOMap {
         IOMap {
+            version: SCHEMA_VERSION,
             grid: self.grid.clone(),
             player_start: self.player_start,
             size: self.size,
+            scrolling: self.scrolling,
         }
     }
     pub fn up(&mut self) {
@@ -371,7 +1238,7 @@ impl Map {
     }
     pub fn grow_vert_plus(&mut self) {
         // might need to change to index 0 if width and height are wrong
-        if self.player_start[1] >= self.current[1] + 1 {
+        if self.player_start[1] > self.current[1] {
             self.player_start[1] += 1;
         }
         self.grid
@@ -386,7 +1253,7 @@ impl Map {
         // might need to change to index 0 if width and height are wrong
         if self.player_start[1] == self.current[1] {
             self.player_start = [0; 4];
-            self.grid[0][0].contents[0][0] = GridSpace::StartingLocation;
+            self.grid[0][0].contents[0][0] = GridSpace::start();
         } else if self.player_start[1] > self.current[1] {
             self.player_start[1] -= 1;
         }
@@ -408,7 +1275,7 @@ impl Map {
     }
     pub fn grow_horizon_plus(&mut self) {
         // might need to change to index 1 if width and height are wrong
-        if self.player_start[0] >= self.current[0] + 1 {
+        if self.player_start[0] > self.current[0] {
             self.player_start[0] += 1;
         }
         for line in &mut self.grid {
@@ -420,7 +1287,7 @@ impl Map {
         // might need to change to index 1 if width and height are wrong
         if self.player_start[0] == self.current[0] {
             self.player_start = [0; 4];
-            self.grid[0][0].contents[0][0] = GridSpace::StartingLocation;
+            self.grid[0][0].contents[0][0] = GridSpace::start();
         } else if self.player_start[0] > self.current[0] {
             self.player_start[0] -= 1;
         }
@@ -439,7 +1306,7 @@ impl Map {
     pub fn clear(&mut self, jobs: &mut RenderJobs) {
         self.current = [0, 0];
         self.grid = vec![vec![LevelGrid::new()]];
-        self.grid[0][0].contents[0][0] = GridSpace::StartingLocation;
+        self.grid[0][0].contents[0][0] = GridSpace::start();
         self.player_start = [0; 4];
         self.size = [1, 1];
         self.renderer.clear(jobs);
@@ -448,24 +1315,20 @@ impl Map {
 pub struct MousePos {
     _raw: [f64; 2],
     grid_location: Option<[usize; 2]>,
+    exact: Option<[f64; 2]>, // where on the grid the mouse is, in tiles (not rounded)
     guide_location: Option<[usize; 2]>,
 }
 impl MousePos {
-    pub fn get(raw: [f64; 2]) -> MousePos {
+    pub fn get(raw: [f64; 2], view: &View) -> MousePos {
         let mouse_location_tiles = [
             (raw[0] / MEDIT_TILE_SIZE).floor(),
             (raw[1] / MEDIT_TILE_SIZE).floor(),
         ];
-        let grid_location = if mouse_location_tiles[0] < (MEDIT_TILES as f64)
-            && mouse_location_tiles[1] < (MEDIT_TILES as f64)
-        {
-            Some([
-                mouse_location_tiles[0] as usize,
-                mouse_location_tiles[1] as usize,
-            ])
-        } else {
-            None
-        };
+        // the grid is zoomed and panned, unlike everything else
+        let exact = MousePos::on_grid(raw)
+            .then(|| view.to_tiles(raw))
+            .filter(|x| x.iter().all(|x| (0.0..MEDIT_TILES as f64).contains(x)));
+        let grid_location = exact.map(|x| x.map(|x| x.floor() as usize));
         let guide_location = if mouse_location_tiles[0] >= (MEDIT_TILES as f64)
             && mouse_location_tiles[1] < (MEDIT_TILES as f64)
             && mouse_location_tiles[0] < ((MEDIT_TILES as f64) + (MEDIT_GUIDE_SIZE as f64))
@@ -480,7 +1343,115 @@ impl MousePos {
         MousePos {
             _raw: raw,
             grid_location,
+            exact,
             guide_location,
         }
     }
+    // whether a position in pixels is in the grid's part of the window
+    pub fn on_grid(raw: [f64; 2]) -> bool {
+        let room = MEDIT_TILES as f64 * MEDIT_TILE_SIZE;
+        raw.iter().all(|x| (0.0..room).contains(x))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_levels_round_trip() {
+        let (io_map, format) = IOMap::load("assets/levels/l2").unwrap();
+        let json = io_map.to_bytes(MapFormat::Json).unwrap();
+        let binary = io_map.to_bytes(MapFormat::Binary).unwrap();
+        assert!(binary.len() < json.len());
+        let (read, read_format) = IOMap::from_bytes(&binary).unwrap();
+        assert_eq!(read_format, MapFormat::Binary);
+        assert_eq!(read.to_bytes(MapFormat::Json).unwrap(), json);
+        // and back to where it started
+        let (read, _) = IOMap::from_bytes(&read.to_bytes(format).unwrap()).unwrap();
+        assert_eq!(read.to_bytes(MapFormat::Binary).unwrap(), binary);
+    }
+    #[test]
+    fn detects_formats() {
+        assert_eq!(MapFormat::detect(b"GAML\x02\0\0\0"), MapFormat::Binary);
+        assert_eq!(MapFormat::detect(b"\n  {\"grid\": []}"), MapFormat::Json);
+        assert_eq!(MapFormat::detect(b"version 1\n"), MapFormat::Ascii);
+    }
+    #[test]
+    fn refuses_cut_short_binary_levels() {
+        let binary = IOMap::new().to_bytes(MapFormat::Binary).unwrap();
+        assert!(IOMap::from_bytes(&binary[..6]).is_err());
+        assert!(IOMap::from_bytes(&binary[..binary.len() - 1]).is_err());
+    }
+    #[test]
+    fn levels_remember_scrolling() {
+        let mut io_map = IOMap::new();
+        io_map.scrolling = true;
+        for format in [MapFormat::Json, MapFormat::Binary, MapFormat::Ascii] {
+            let (read, _) = IOMap::from_bytes(&io_map.to_bytes(format).unwrap()).unwrap();
+            assert!(read.scrolling);
+        }
+        assert!(io_map.into_level().scrolling);
+        // levels from before then don't scroll
+        let (io_map, _) = IOMap::load("assets/levels/l1").unwrap();
+        assert!(!io_map.into_level().scrolling);
+    }
+    #[test]
+    fn pasting_off_the_grid_keeps_the_clipboard() {
+        let mut jobs = RenderJobs::new();
+        let mut map = Map::new(PathBuf::from("unsaved"), &mut jobs);
+        map.selection = Some([[1, 1], [2, 3]]);
+        map.copy();
+        assert!(map.clipboard.is_some());
+        let off_grid = MousePos::get(
+            [(MEDIT_TILES + 1) as f64 * MEDIT_TILE_SIZE, 10.0],
+            &View::new(),
+        );
+        assert!(off_grid.grid_location.is_none());
+        map.paste(&mut jobs, &off_grid);
+        assert!(map.clipboard.is_some());
+        // pasting for real still leaves it there for next time
+        let on_grid = MousePos::get([20.0, 20.0], &View::new());
+        assert!(on_grid.grid_location.is_some());
+        map.paste(&mut jobs, &on_grid);
+        assert!(map.clipboard.is_some());
+    }
+    #[test]
+    fn playtests_start_on_this_screen() {
+        let mut jobs = RenderJobs::new();
+        let mut map = Map::new(PathBuf::from("unsaved"), &mut jobs);
+        map.grid[0].push(LevelGrid::new());
+        map.size = [2, 1];
+        map.player_start = [0, 0, 4, 7];
+        map.grid[0][0].contents[4][7] = GridSpace::start();
+        map.current = [1, 0];
+        let off_grid = MousePos::get([-10.0, -10.0], &View::new());
+        assert!(off_grid.grid_location.is_none());
+        let io_map = map.playtest_map(&off_grid);
+        // the start moved over from the other screen, to the same spot
+        assert_eq!(io_map.player_start, [1, 0, 4, 7]);
+        assert_eq!(io_map.grid[0][1].contents[4][7], GridSpace::start());
+        assert_eq!(io_map.grid[0][0].contents[4][7], GridSpace::empty());
+        // and it's under the cursor when there is one
+        let on_grid = MousePos::get([20.0, 50.0], &View::new());
+        let [column, row] = on_grid.grid_location.unwrap();
+        let io_map = map.playtest_map(&on_grid);
+        assert_eq!(io_map.player_start, [1, 0, row, column]);
+        assert_eq!(io_map.grid[0][1].contents[4][7], GridSpace::empty());
+        assert_eq!(io_map.grid[0][0].contents[4][7], GridSpace::empty());
+    }
+    #[test]
+    fn panels_are_beside_the_guide() {
+        use consts::{MEDIT_HUD, MEDIT_MINIMAP, MEDIT_WINDOW_X, MEDIT_WINDOW_Y};
+        for [x, y, width, height] in [MEDIT_MINIMAP, MEDIT_HUD] {
+            assert!(x >= MEDIT_TILES + MEDIT_GUIDE_SIZE);
+            assert!((x + width) as f64 * MEDIT_TILE_SIZE <= MEDIT_WINDOW_X as f64);
+            assert!((y + height) as f64 * MEDIT_TILE_SIZE <= MEDIT_WINDOW_Y as f64);
+        }
+        // the window isn't a few pixels short from the tile size being rounded down
+        assert_eq!(MEDIT_WINDOW_Y as f64, MEDIT_TILES as f64 * MEDIT_TILE_SIZE);
+        let corner = [MEDIT_MINIMAP[0], MEDIT_MINIMAP[1]].map(|x| x as f64 * MEDIT_TILE_SIZE + 1.0);
+        let mouse_pos = MousePos::get(corner, &View::new());
+        assert!(mouse_pos.guide_location.is_none());
+        assert_eq!(minimap::screen_at(corner, [1, 1]), Some([0, 0]));
+    }
 }
@@ -0,0 +1,262 @@
+/**
+ * The ascii level format - for sketching screens in a text editor and reading level diffs.
+ *
+ * A level is a list of directives, one per line, followed by the screens:
+ *  version [n] - the level's schema version (see migrate::SCHEMA_VERSION). Left out means 0.
+ *  size [width] [height] - how many screens across and down the level is.
+ *  start [screen x] [screen y] [row] [column] - where the player starts. Left out means wherever the
+ *      starting location tile is.
+ *  scrolling [0 or 1] - whether the screens are put together into one area that scrolls (see
+ *      Level::scrolling). Left out means 0.
+ *  screen [x] [y] - starts a screen. The lines after it are its rows of tiles, top to bottom,
+ *      one character per tile (see the symbols in assets/tiles.json). A screen has TILES + 2 rows
+ *      of TILES + 2 tiles, counting the border. Every screen in the level needs to be there, in
+ *      any order.
+ *  other [json] - a block template that's a part of the screen above it (text, decorations...)
+ *  platform [json] - a moving platform that's a part of the screen above it
+ * Blank lines and lines starting with # are ignored. Rows never contain spaces, so any line with
+ * a space in it is a directive.
+ *
+ * For example, the start of a level with one screen (with its 52 rows of 52 tiles cut short):
+ *  version 2
+ *  size 1 1
+ *  start 0 0 1 1
+ *  scrolling 0
+ *  screen 0 0
+ *  BBBB...
+ *  BP__...
+ *  ...
+ */
+use std::fmt::Write;
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    consts::TILES,
+    internals::{
+        levels::{GridSpace, LevelGrid},
+        tiles::tiles,
+    },
+};
+
+use super::IOMap;
+
+// Reads an ascii level into the json form of an IOMap, so that it can be migrated like any other.
+pub fn import(text: &str) -> Result<Value, String> {
+    let mut version = 0;
+    let mut size = None;
+    let mut start = None;
+    let mut scrolling = false;
+    // [x, y] of each screen, the screen itself, and the line it starts on
+    let mut screens: Vec<([usize; 2], Map<String, Value>, usize)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        let error = |x: String| format!("Line {}: {}", i + 1, x);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((directive, rest)) = line.split_once(' ') else {
+            // a row of tiles
+            let (_, screen, _) = screens
+                .last_mut()
+                .ok_or_else(|| error("Tiles need to come after a screen".to_string()))?;
+            let rows = screen["contents"].as_array_mut().expect("Safe unwrap");
+            if rows.len() == TILES + 2 {
+                return Err(error(format!("A screen only has {} rows", TILES + 2)));
+            }
+            if line.chars().count() != TILES + 2 {
+                return Err(error(format!(
+                    "A row needs {} tiles, but this one has {}",
+                    TILES + 2,
+                    line.chars().count()
+                )));
+            }
+            let row = line
+                .chars()
+                .map(|c| {
+                    tiles()
+                        .with_symbol(c)
+                        .map(|x| json!(x.def().name))
+                        .ok_or_else(|| error(format!("There's no tile drawn as {}", c)))
+                })
+                .collect::<Result<Vec<Value>, String>>()?;
+            rows.push(json!(row));
+            continue;
+        };
+        match directive {
+            "version" => version = parse::<1>(rest).map_err(error)?[0] as u64,
+            "size" => size = Some(parse::<2>(rest).map_err(error)?),
+            "start" => start = Some(parse::<4>(rest).map_err(error)?),
+            "scrolling" => scrolling = parse::<1>(rest).map_err(error)?[0] != 0,
+            "screen" => {
+                let pos = parse::<2>(rest).map_err(error)?;
+                if screens.iter().any(|x| x.0 == pos) {
+                    return Err(error(format!("Screen {:?} is there twice", pos)));
+                }
+                let mut screen = Map::new();
+                screen.insert("contents".to_string(), json!([]));
+                screen.insert("others".to_string(), json!([]));
+                screen.insert("platforms".to_string(), json!([]));
+                screens.push((pos, screen, i + 1));
+            }
+            "other" | "platform" => {
+                let (_, screen, _) = screens.last_mut().ok_or_else(|| {
+                    error(format!("A {} needs to come after a screen", directive))
+                })?;
+                let value: Value = serde_json::from_str(rest).map_err(|x| error(x.to_string()))?;
+                let list = if directive == "other" {
+                    "others"
+                } else {
+                    "platforms"
+                };
+                screen[list]
+                    .as_array_mut()
+                    .expect("Safe unwrap")
+                    .push(value);
+            }
+            _ => return Err(error(format!("Unknown directive {}", directive))),
+        }
+    }
+    let size = size.ok_or("The level needs a size")?;
+    let mut grid = vec![vec![Value::Null; size[0]]; size[1]];
+    for ([x, y], screen, line) in screens {
+        let rows = screen["contents"].as_array().expect("Safe unwrap").len();
+        if rows != TILES + 2 {
+            return Err(format!(
+                "Line {}: Screen {:?} has {} rows, but needs {}",
+                line,
+                [x, y],
+                rows,
+                TILES + 2
+            ));
+        }
+        let spot = grid
+            .get_mut(y)
+            .and_then(|line| line.get_mut(x))
+            .ok_or_else(|| format!("Screen {:?} is outside of the level", [x, y]))?;
+        *spot = Value::Object(screen);
+    }
+    for (y, line) in grid.iter().enumerate() {
+        if let Some(x) = line.iter().position(Value::is_null) {
+            return Err(format!("Screen {:?} is missing", [x, y]));
+        }
+    }
+    let start = match start {
+        Some(val) => val,
+        None => find_start(&grid)?,
+    };
+    Ok(json!({
+        "version": version,
+        "grid": grid,
+        "player_start": start,
+        "size": size,
+        "scrolling": scrolling,
+    }))
+}
+
+// Writes a level out as ascii.
+pub fn export(io_map: &IOMap) -> Result<String, String> {
+    let mut res = String::new();
+    let [x, y] = io_map.size;
+    writeln!(res, "version {}", io_map.version).expect("Safe unwrap");
+    writeln!(res, "size {} {}", x, y).expect("Safe unwrap");
+    let [a, b, c, d] = io_map.player_start;
+    writeln!(res, "start {} {} {} {}", a, b, c, d).expect("Safe unwrap");
+    writeln!(res, "scrolling {}", io_map.scrolling as u8).expect("Safe unwrap");
+    for (y, line) in io_map.grid.iter().enumerate() {
+        for (x, screen) in line.iter().enumerate() {
+            writeln!(res, "screen {} {}", x, y).expect("Safe unwrap");
+            export_screen(&mut res, screen)?;
+        }
+    }
+    Ok(res)
+}
+fn export_screen(res: &mut String, screen: &LevelGrid) -> Result<(), String> {
+    for row in &screen.contents {
+        res.extend(row.iter().map(|x| x.def().symbol));
+        res.push('\n');
+    }
+    for other in &screen.others {
+        let other = serde_json::to_string(other).map_err(|x| x.to_string())?;
+        writeln!(res, "other {}", other).expect("Safe unwrap");
+    }
+    for platform in &screen.platforms {
+        let platform = serde_json::to_string(platform).map_err(|x| x.to_string())?;
+        writeln!(res, "platform {}", platform).expect("Safe unwrap");
+    }
+    Ok(())
+}
+
+// reads N numbers separated by spaces
+fn parse<const N: usize>(text: &str) -> Result<[usize; N], String> {
+    let numbers = text
+        .split_whitespace()
+        .map(|x| x.parse().map_err(|_| format!("{} isn't a number", x)))
+        .collect::<Result<Vec<usize>, String>>()?;
+    numbers
+        .try_into()
+        .map_err(|_| format!("Expected {} numbers, found {}", N, text))
+}
+
+// where the starting location tile is, as a player_start
+fn find_start(grid: &[Vec<Value>]) -> Result<[usize; 4], String> {
+    let start = json!(GridSpace::start().def().name);
+    for (y, line) in grid.iter().enumerate() {
+        for (x, screen) in line.iter().enumerate() {
+            let rows = screen["contents"].as_array().expect("Safe unwrap");
+            for (row, tiles) in rows.iter().enumerate() {
+                let tiles = tiles.as_array().expect("Safe unwrap");
+                if let Some(column) = tiles.iter().position(|x| *x == start) {
+                    return Ok([x, y, row, column]);
+                }
+            }
+        }
+    }
+    Err("The level has no start, and no starting location to start at".to_string())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::medit::MapFormat;
+
+    // a level with one screen of nothing but a border, and the start in the top left
+    fn small_level() -> String {
+        let border = "B".repeat(TILES + 2);
+        let middle = format!("B{}B", "_".repeat(TILES));
+        let mut rows = vec![middle; TILES + 2];
+        rows[0] = border.clone();
+        rows[TILES + 1] = border;
+        rows[1].replace_range(1..2, "P");
+        format!("version 1\nsize 1 1\nscreen 0 0\n{}\n", rows.join("\n"))
+    }
+    #[test]
+    fn ascii_levels_round_trip() {
+        let (io_map, _) = IOMap::load("assets/levels/l1").unwrap();
+        let text = export(&io_map).unwrap();
+        let (read, format) = IOMap::from_bytes(text.as_bytes()).unwrap();
+        assert_eq!(format, MapFormat::Ascii);
+        assert_eq!(
+            read.to_bytes(MapFormat::Json),
+            io_map.to_bytes(MapFormat::Json)
+        );
+        assert_eq!(export(&read).unwrap(), text);
+    }
+    #[test]
+    fn finds_the_start() {
+        let level = import(&small_level()).unwrap();
+        assert_eq!(level["player_start"], json!([0, 0, 1, 1]));
+    }
+    #[test]
+    fn names_the_line_of_bad_rows() {
+        let level = small_level();
+        // a row that's too short, on line 6
+        let short = level.replacen(&format!("B{}B", "_".repeat(TILES)), "B_B", 1);
+        assert!(import(&short).unwrap_err().starts_with("Line 6:"));
+        // one row too many, on line 56
+        let long = format!("{}{}\n", level, "B".repeat(TILES + 2));
+        assert!(import(&long).unwrap_err().starts_with("Line 56:"));
+        // one row too few, for the screen on line 3
+        let cut = level.rsplit_once(&"B".repeat(TILES + 2)).unwrap().0;
+        assert!(import(cut).unwrap_err().starts_with("Line 3:"));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::internals::levels::GridSpace;
+
+// Tiles that were copied in the editor. It lives as long as the editor does, so it can be pasted
+// into any screen of any level opened afterwards.
+pub struct Clipboard {
+    pub tiles: Vec<Vec<GridSpace>>, // [row][column], starting from the top left of the selection
+}
+impl Clipboard {
+    // copies the rectangle between two corners ([row, column]) of a screen
+    pub fn copy(contents: &[Vec<GridSpace>], corners: [[usize; 2]; 2]) -> Clipboard {
+        let [[top, left], [bottom, right]] = bounds(corners);
+        Clipboard {
+            tiles: contents[top..=bottom]
+                .iter()
+                .map(|row| row[left..=right].to_vec())
+                .collect(),
+        }
+    }
+    // every tile with where it goes ([row, column]) when pasted with its top left at pos
+    pub fn paste(&self, pos: [usize; 2]) -> impl Iterator<Item = ([usize; 2], GridSpace)> + '_ {
+        self.tiles.iter().enumerate().flat_map(move |(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(j, tile)| ([pos[0] + i, pos[1] + j], *tile))
+        })
+    }
+}
+
+// the top left and bottom right of the rectangle between two corners
+pub fn bounds(corners: [[usize; 2]; 2]) -> [[usize; 2]; 2] {
+    let [a, b] = corners;
+    [
+        [a[0].min(b[0]), a[1].min(b[1])],
+        [a[0].max(b[0]), a[1].max(b[1])],
+    ]
+}
@@ -0,0 +1,305 @@
+use std::collections::VecDeque;
+
+use bincode::Options;
+
+use crate::{
+    consts::MEDIT_HISTORY_LIMIT,
+    internals::levels::{GridSpace, LevelGrid},
+};
+
+// A single tile changing. spot is [screen x, screen y, row, column], like IOMap::player_start.
+#[derive(Clone, Copy)]
+pub struct TileEdit {
+    pub spot: [usize; 4],
+    pub old: GridSpace,
+    pub new: GridSpace,
+}
+// Everything about the level that can change, for edits that move screens around.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub grid: Vec<Vec<LevelGrid>>,
+    pub size: [usize; 2],
+    pub player_start: [usize; 4],
+    pub current: [usize; 2],
+    pub scrolling: bool,
+}
+// One step of the undo history.
+#[derive(Clone)]
+pub enum Change {
+    // tiles being painted, and possibly the start moving ([old, new])
+    Tiles {
+        tiles: Vec<TileEdit>,
+        start: Option<[[usize; 4]; 2]>,
+    },
+    // screens being added, removed or cleared, or objects being changed
+    Level(Box<LevelDiff>),
+}
+// The part of the level that an edit changed. Rows and columns of screens that are the same before
+// and after are left out, so adding a row only keeps that row.
+#[derive(Clone)]
+pub struct LevelDiff {
+    rows: [usize; 2],     // how many rows of screens at the top and bottom didn't change
+    columns: [usize; 2],  // how many columns at the left and right of the other rows didn't change
+    sides: [Snapshot; 2], // before and after, with only the screens that changed
+}
+impl LevelDiff {
+    // None if the edit didn't change anything.
+    pub fn new(before: Snapshot, after: Snapshot) -> Option<LevelDiff> {
+        let mut sides = [before, after];
+        let [a, b] = [&sides[0].grid, &sides[1].grid];
+        let same_row = |i: usize, j: usize| {
+            a[i].len() == b[j].len() && a[i].iter().zip(&b[j]).all(|(a, b)| same(a, b))
+        };
+        let height = a.len().min(b.len());
+        let top = (0..height).take_while(|&i| same_row(i, i)).count();
+        let bottom = (0..height - top)
+            .take_while(|&i| same_row(a.len() - 1 - i, b.len() - 1 - i))
+            .count();
+        let middle = [&a[top..a.len() - bottom], &b[top..b.len() - bottom]];
+        // columns can only be left out if the same rows are left on both sides
+        let mut columns = [0, 0];
+        if middle[0].len() == middle[1].len() && !middle[0].is_empty() {
+            let widths = middle.map(|x| x[0].len());
+            let same_column = |i: usize, j: usize| {
+                middle[0]
+                    .iter()
+                    .zip(middle[1])
+                    .all(|(a, b)| same(&a[i], &b[j]))
+            };
+            let width = widths[0].min(widths[1]);
+            columns[0] = (0..width).take_while(|&i| same_column(i, i)).count();
+            columns[1] = (0..width - columns[0])
+                .take_while(|&i| same_column(widths[0] - 1 - i, widths[1] - 1 - i))
+                .count();
+        }
+        for side in &mut sides {
+            let end = side.grid.len() - bottom;
+            side.grid = side.grid.drain(top..end).collect();
+            for row in &mut side.grid {
+                let end = row.len() - columns[1];
+                *row = row.drain(columns[0]..end).collect();
+            }
+        }
+        let unchanged = sides.iter().all(|x| x.grid.iter().all(Vec::is_empty));
+        let [a, b] = &sides;
+        if unchanged && a.player_start == b.player_start && a.scrolling == b.scrolling {
+            return None;
+        }
+        Some(LevelDiff {
+            rows: [top, bottom],
+            columns,
+            sides,
+        })
+    }
+    // Takes the level as it is after the change (or before, when redoing), and gives it back as it
+    // was before (or after).
+    pub fn apply(self, mut level: Snapshot, undo: bool) -> Snapshot {
+        let [before, after] = self.sides;
+        let side = if undo { before } else { after };
+        let ([top, bottom], [left, right]) = (self.rows, self.columns);
+        let end = level.grid.len() - bottom;
+        if left == 0 && right == 0 {
+            level.grid.splice(top..end, side.grid);
+        } else {
+            for (row, screens) in level.grid[top..end].iter_mut().zip(side.grid) {
+                let end = row.len() - right;
+                row.splice(left..end, screens);
+            }
+        }
+        Snapshot {
+            grid: level.grid,
+            ..side
+        }
+    }
+}
+// whether two screens are the same, going by how they're saved
+fn same(a: &LevelGrid, b: &LevelGrid) -> bool {
+    let options = bincode::DefaultOptions::new();
+    options.serialize(a).ok() == options.serialize(b).ok()
+}
+
+// Every change is kept with an id, so that the level can be told apart from how it was before.
+pub struct History {
+    undo: VecDeque<(u64, Change)>,
+    redo: Vec<(u64, Change)>,
+    // tile edits that are still being made (i.e. the mouse is still being dragged)
+    group: Option<Change>,
+    next_id: u64,
+    // The id of the state below the oldest change. Once old changes are forgotten, undoing
+    // everything doesn't get back to how the level started.
+    oldest: u64,
+}
+impl History {
+    pub fn new() -> History {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            group: None,
+            next_id: 1,
+            oldest: 0,
+        }
+    }
+    // Identifies what the level looks like: undoing back to a state gives the same id again, and
+    // any new change gives a new one. None while tiles are still being painted.
+    pub fn state(&self) -> Option<u64> {
+        match self.group {
+            Some(_) => None,
+            None => Some(self.undo.back().map_or(self.oldest, |x| x.0)),
+        }
+    }
+    // adds a tile edit to the current group, starting a group if there isn't one.
+    pub fn tile(&mut self, edit: TileEdit) {
+        if edit.old == edit.new {
+            return;
+        }
+        if let Change::Tiles { tiles, .. } = self.group() {
+            tiles.push(edit);
+        }
+    }
+    // adds the start moving to the current group.
+    pub fn start(&mut self, old: [usize; 4], new: [usize; 4]) {
+        if let Change::Tiles { start, .. } = self.group() {
+            match start {
+                Some(val) => val[1] = new,
+                None => *start = Some([old, new]),
+            }
+        }
+    }
+    fn group(&mut self) -> &mut Change {
+        self.group.get_or_insert_with(|| Change::Tiles {
+            tiles: Vec::new(),
+            start: None,
+        })
+    }
+    // finishes the current group, so that it's undone in one step.
+    pub fn end_group(&mut self) {
+        if let Some(change) = self.group.take() {
+            if let Change::Tiles { tiles, start: None } = &change {
+                if tiles.is_empty() {
+                    return;
+                }
+            }
+            self.push(change);
+        }
+    }
+    pub fn push(&mut self, change: Change) {
+        self.end_group();
+        self.undo.push_back((self.next_id, change));
+        self.next_id += 1;
+        if self.undo.len() > MEDIT_HISTORY_LIMIT {
+            if let Some((id, _)) = self.undo.pop_front() {
+                self.oldest = id;
+            }
+        }
+        self.redo.clear();
+    }
+    // The change to undo. It's moved onto the redo stack.
+    pub fn undo(&mut self) -> Option<Change> {
+        self.end_group();
+        let change = self.undo.pop_back()?;
+        self.redo.push(change.clone());
+        Some(change.1)
+    }
+    // The change to redo. It's moved back onto the undo stack.
+    pub fn redo(&mut self) -> Option<Change> {
+        self.end_group();
+        let change = self.redo.pop()?;
+        self.undo.push_back(change.clone());
+        Some(change.1)
+    }
+}
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a level of width x height screens, with a different tile in each screen so they can be told apart
+    fn level(width: usize, height: usize) -> Snapshot {
+        let grid = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let mut screen = LevelGrid::new();
+                        screen.contents[y][x] = GridSpace::start();
+                        screen
+                    })
+                    .collect()
+            })
+            .collect();
+        Snapshot {
+            grid,
+            size: [width, height],
+            player_start: [0; 4],
+            current: [0, 0],
+            scrolling: false,
+        }
+    }
+    fn same_level(a: &Snapshot, b: &Snapshot) -> bool {
+        a.size == b.size
+            && a.grid.len() == b.grid.len()
+            && a.grid
+                .iter()
+                .zip(&b.grid)
+                .all(|(a, b)| a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b)))
+    }
+
+    #[test]
+    fn keeps_only_what_changed() {
+        let before = level(3, 3);
+        let mut after = before.clone();
+        after.grid.insert(1, vec![LevelGrid::new(); 3]);
+        after.size[1] += 1;
+        let diff = LevelDiff::new(before.clone(), after.clone()).unwrap();
+        assert_eq!(diff.sides[0].grid.len(), 0);
+        assert_eq!(diff.sides[1].grid.len(), 1);
+        let undone = diff.clone().apply(after.clone(), true);
+        assert!(same_level(&undone, &before));
+        assert!(same_level(&diff.apply(undone, false), &after));
+
+        let mut after = before.clone();
+        for row in &mut after.grid {
+            row.remove(2);
+        }
+        after.size[0] -= 1;
+        let diff = LevelDiff::new(before.clone(), after.clone()).unwrap();
+        assert!(diff.sides[0].grid.iter().all(|x| x.len() == 1));
+        assert!(diff.sides[1].grid.iter().all(Vec::is_empty));
+        assert!(same_level(&diff.apply(after, true), &before));
+    }
+    #[test]
+    fn skips_edits_that_change_nothing() {
+        let before = level(2, 2);
+        assert!(LevelDiff::new(before.clone(), before.clone()).is_none());
+        let mut after = before.clone();
+        after.current = [1, 1];
+        assert!(LevelDiff::new(before.clone(), after.clone()).is_none());
+        after.scrolling = true;
+        let diff = LevelDiff::new(before, after).unwrap();
+        assert!(!diff.apply(level(2, 2), true).scrolling);
+    }
+    #[test]
+    fn forgets_the_oldest_changes() {
+        let mut history = History::new();
+        for i in 0..MEDIT_HISTORY_LIMIT + 10 {
+            history.tile(TileEdit {
+                spot: [0, 0, i % 10, 0],
+                old: GridSpace::empty(),
+                new: GridSpace::start(),
+            });
+            history.end_group();
+        }
+        let mut count = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, MEDIT_HISTORY_LIMIT);
+        // some of the changes can't be undone, so it isn't back to how it started
+        assert_ne!(history.state(), Some(0));
+        assert_ne!(history.state(), History::new().state());
+    }
+}
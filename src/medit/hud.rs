@@ -0,0 +1,92 @@
+use std::time::Instant;
+
+use crate::{
+    consts::{
+        DEFAULT_FONT_ID, MEDIT_HUD, MEDIT_HUD_TEXT_SIZE, MEDIT_MESSAGE_TIME, MEDIT_TILE_SIZE,
+        UI_LAYER, WHITE, YELLOW,
+    },
+    render::{text::TextRenderer, RenderJobID, RenderJobs},
+};
+
+// The editor's status bar, in the extra room under the minimap: a few lines about the level and
+// the cursor, followed by the latest message (which goes away after a few seconds).
+pub struct Hud {
+    lines: Vec<RenderJobID>, // one text job per line, top to bottom
+    message: Option<(String, Instant)>,
+}
+impl Hud {
+    pub fn new(jobs: &mut RenderJobs) -> Hud {
+        let [x, y, width, _] = MEDIT_HUD.map(|x| x as f64 * MEDIT_TILE_SIZE);
+        let lines = (0..MEDIT_HUD[3])
+            .map(|i| {
+                let bounds = [x, y + i as f64 * MEDIT_TILE_SIZE, width, MEDIT_TILE_SIZE];
+                let mut job = TextRenderer::new(
+                    String::new(),
+                    bounds,
+                    WHITE,
+                    MEDIT_HUD_TEXT_SIZE,
+                    -1,
+                    0,
+                    DEFAULT_FONT_ID,
+                );
+                TextRenderer::ensure_mut(&mut job).multiline = true;
+                jobs.add_job(job, UI_LAYER)
+            })
+            .collect();
+        Hud {
+            lines,
+            message: None,
+        }
+    }
+    // shows a message for a few seconds, replacing the last one
+    pub fn message(&mut self, text: impl Into<String>) {
+        self.message = Some((text.into(), Instant::now()));
+    }
+    // Shows the status, one entry per line (wrapped to fit), and then the message if there is one.
+    pub fn update(&mut self, jobs: &mut RenderJobs, status: &[String]) {
+        if self
+            .message
+            .as_ref()
+            .is_some_and(|x| x.1.elapsed() > MEDIT_MESSAGE_TIME)
+        {
+            self.message = None;
+        }
+        let status = status.iter().flat_map(|x| wrap(x)).map(|x| (x, WHITE));
+        let message = self.message.iter().flat_map(|x| wrap(&x.0));
+        let mut text = status.chain(message.map(|x| (x, YELLOW)));
+        for id in &self.lines {
+            let (line, color) = text.next().unwrap_or_default();
+            let job = TextRenderer::ensure_mut(jobs.get_job_mut(*id).expect("Safe unwrap"));
+            if job.text != line {
+                job.text = line.into();
+            }
+            job.color = color;
+        }
+    }
+}
+
+// Splits text into lines that fit in the HUD, between words where possible.
+fn wrap(text: &str) -> Vec<String> {
+    // the font is monospaced, with characters about half as wide as they are tall
+    let width =
+        (MEDIT_HUD[2] as f64 * MEDIT_TILE_SIZE / (MEDIT_HUD_TEXT_SIZE as f64 * 0.5)) as usize;
+    let mut res: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let fits = line.chars().count() + word.chars().count() < width;
+        if !line.is_empty() && !fits {
+            res.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+        // words that are too long on their own (like paths) get split up
+        while line.chars().count() > width {
+            let rest = line.split_off(line.char_indices().nth(width).expect("Safe unwrap").0);
+            res.push(std::mem::replace(&mut line, rest));
+        }
+    }
+    res.push(line);
+    res
+}
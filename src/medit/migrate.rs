@@ -0,0 +1,300 @@
+use std::path::Path;
+
+use bincode::Options;
+use serde::{ser, Deserialize, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::internals::object::BlockTemplate;
+
+use super::{ascii, IOMap, MapFormat, BINARY_MAGIC, BINARY_VERSION};
+
+// The version of the level format this build writes. Bump it (and add a migration below) whenever
+// a change would stop older level files from loading: renaming a tile, adding a required field, etc.
+//
+// Binary level files aren't self-describing, so they can't be migrated like this directly. When
+// bumping this, bump BINARY_VERSION too, and add the old binary layout to read_old_binary.
+pub const SCHEMA_VERSION: u32 = 2;
+
+// Each migration upgrades a json level from version i to version i + 1.
+type Migration = fn(&mut Value) -> Result<(), String>;
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+// the version a json level was written at. Levels from before versioning are version 0.
+pub fn version_of(level: &Value) -> Result<u32, String> {
+    match level.get("version") {
+        Some(version) => version
+            .as_u64()
+            .map(|x| x as u32)
+            .ok_or("The level's version isn't a number!".to_string()),
+        None => Ok(0),
+    }
+}
+
+// Upgrades a json level to the current version, one migration at a time.
+pub fn migrate(level: &mut Value) -> Result<(), String> {
+    let version = version_of(level)?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "The level is version {}, which is newer than this game (version {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(level).map_err(|x| format!("Upgrading from version {} failed: {}", i, x))?;
+        level["version"] = json!(i + 1);
+    }
+    Ok(())
+}
+
+// The first binary levels, which were written before levels had versions.
+#[derive(Serialize, Deserialize)]
+struct BinaryV1 {
+    grid: Vec<Vec<OldLevelGrid>>,
+    player_start: [usize; 4],
+    size: [usize; 2],
+}
+// Binary levels from schema version 1, before levels could scroll.
+#[derive(Serialize, Deserialize)]
+struct BinaryV2 {
+    version: u32,
+    grid: Vec<Vec<OldLevelGrid>>,
+    player_start: [usize; 4],
+    size: [usize; 2],
+}
+// The layouts below are copies of what binary versions 1 and 2 were written with, so that changing
+// the live types (LevelGrid, PlatformTemplate...) doesn't change how old files are read.
+// Block templates are still read as they are now; they haven't changed since.
+#[derive(Serialize, Deserialize)]
+struct OldLevelGrid {
+    contents: Vec<Vec<OldTile>>,
+    others: Vec<BlockTemplate>,
+    platforms: Vec<OldPlatform>,
+}
+#[derive(Serialize, Deserialize)]
+struct OldPlatform {
+    block: BlockTemplate,
+    path: Vec<[f64; 2]>,
+    speed: f64,
+    looping: bool,
+}
+// what the tile ids meant back then
+const OLD_TILES: [&str; 14] = [
+    "None",
+    "Block",
+    "Spike",
+    "Enemy",
+    "Goal",
+    "StartingLocation",
+    "Transition",
+    "Wrap",
+    "StickyBlock",
+    "ConveyorR",
+    "ConveyorL",
+    "Slime",
+    "Water",
+    "Flipper",
+];
+// A tile, stored by id. It's written to json by name, so the json can be migrated.
+#[derive(Deserialize)]
+struct OldTile(u64);
+impl Serialize for OldTile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_u64(self.0);
+        }
+        match OLD_TILES.get(self.0 as usize) {
+            Some(name) => serializer.serialize_str(name),
+            None => Err(ser::Error::custom(format!("unknown tile id {}", self.0))),
+        }
+    }
+}
+// Reads a binary level written at an older BINARY_VERSION (without the header) into json, so that it
+// can be migrated like any other older level.
+pub fn read_old_binary(version: u32, data: &[u8]) -> Result<Value, String> {
+    let options = bincode::DefaultOptions::new();
+    match version {
+        // from before versioning, so it's version 0 (see version_of)
+        1 => {
+            let level: BinaryV1 = options.deserialize(data).map_err(|x| x.to_string())?;
+            serde_json::to_value(level).map_err(|x| x.to_string())
+        }
+        2 => {
+            let level: BinaryV2 = options.deserialize(data).map_err(|x| x.to_string())?;
+            serde_json::to_value(level).map_err(|x| x.to_string())
+        }
+        _ => Err(format!(
+            "Unsupported binary level version {} (expected {} or older)",
+            version, BINARY_VERSION
+        )),
+    }
+}
+// the BINARY_VERSION a binary level was written at
+pub fn binary_version(data: &[u8]) -> Result<u32, String> {
+    let version = data
+        .get(BINARY_MAGIC.len()..BINARY_MAGIC.len() + 4)
+        .ok_or("Binary level file is cut short!")?;
+    Ok(u32::from_le_bytes(version.try_into().expect("Safe unwrap")))
+}
+
+// Rewrites a level file at the current version, in the same format it was in.
+// Returns the version it used to be, or None if it was already up to date.
+pub fn upgrade_file(path: impl AsRef<Path>) -> Result<Option<u32>, String> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|x| x.to_string())?;
+    let format = MapFormat::detect(&data);
+    let version = match format {
+        MapFormat::Binary => match binary_version(&data)? {
+            BINARY_VERSION => return Ok(None),
+            version => version_of(&read_old_binary(version, &data[BINARY_MAGIC.len() + 4..])?)?,
+        },
+        MapFormat::Json => version_of(&serde_json::from_slice(&data).map_err(|x| x.to_string())?)?,
+        MapFormat::Ascii => version_of(&ascii::import(
+            std::str::from_utf8(&data).map_err(|x| x.to_string())?,
+        )?)?,
+    };
+    if version == SCHEMA_VERSION {
+        return Ok(None);
+    }
+    let (io_map, _) = IOMap::from_bytes(&data)?;
+    io_map.save(path, format)?;
+    Ok(Some(version))
+}
+
+// every screen of a level, wherever it is in the grid
+fn screens(level: &mut Value) -> Result<impl Iterator<Item = &mut Value>, String> {
+    let grid = level
+        .get_mut("grid")
+        .and_then(Value::as_array_mut)
+        .ok_or("The level has no grid!")?;
+    Ok(grid.iter_mut().filter_map(Value::as_array_mut).flatten())
+}
+
+// version 1 added moving platforms. Screens from before then don't have any.
+fn v0_to_v1(level: &mut Value) -> Result<(), String> {
+    for screen in screens(level)? {
+        let screen = screen.as_object_mut().ok_or("A screen isn't an object!")?;
+        screen.entry("platforms").or_insert(json!([]));
+    }
+    Ok(())
+}
+// version 2 let levels scroll, which they didn't before
+fn v1_to_v2(level: &mut Value) -> Result<(), String> {
+    let level = level.as_object_mut().ok_or("The level isn't an object!")?;
+    level.entry("scrolling").or_insert(json!(false));
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internals::levels::LevelGrid;
+
+    fn level() -> IOMap {
+        IOMap::load("assets/levels/l1").unwrap().0
+    }
+    // the screens as they would have been written before
+    fn old_grid(io_map: &IOMap) -> Vec<Vec<OldLevelGrid>> {
+        let old_screen = |screen: &LevelGrid| OldLevelGrid {
+            contents: screen
+                .contents
+                .iter()
+                .map(|row| row.iter().map(|x| OldTile(x.0 as u64)).collect())
+                .collect(),
+            others: screen.others.clone(),
+            platforms: screen
+                .platforms
+                .iter()
+                .map(|x| OldPlatform {
+                    block: x.block.clone(),
+                    path: x.path.clone(),
+                    speed: x.speed,
+                    looping: x.looping,
+                })
+                .collect(),
+        };
+        io_map
+            .grid
+            .iter()
+            .map(|row| row.iter().map(old_screen).collect())
+            .collect()
+    }
+    // the level as it would have been written at binary version 1
+    fn v1_binary(io_map: &IOMap) -> Vec<u8> {
+        let mut data = BINARY_MAGIC.to_vec();
+        data.extend(1u32.to_le_bytes());
+        let old = BinaryV1 {
+            grid: old_grid(io_map),
+            player_start: io_map.player_start,
+            size: io_map.size,
+        };
+        bincode::DefaultOptions::new()
+            .serialize_into(&mut data, &old)
+            .unwrap();
+        data
+    }
+    #[test]
+    fn reads_v1_binary_levels() {
+        let io_map = level();
+        let (read, format) = IOMap::from_bytes(&v1_binary(&io_map)).unwrap();
+        assert_eq!(format, MapFormat::Binary);
+        assert_eq!(read.version, SCHEMA_VERSION);
+        assert_eq!(
+            read.to_bytes(MapFormat::Json),
+            io_map.to_bytes(MapFormat::Json)
+        );
+    }
+    #[test]
+    fn reads_v2_binary_levels() {
+        let io_map = level();
+        let mut data = BINARY_MAGIC.to_vec();
+        data.extend(2u32.to_le_bytes());
+        let old = BinaryV2 {
+            version: 1,
+            grid: old_grid(&io_map),
+            player_start: io_map.player_start,
+            size: io_map.size,
+        };
+        bincode::DefaultOptions::new()
+            .serialize_into(&mut data, &old)
+            .unwrap();
+        let (read, _) = IOMap::from_bytes(&data).unwrap();
+        assert_eq!(read.version, SCHEMA_VERSION);
+        assert!(!read.scrolling);
+        assert_eq!(
+            read.to_bytes(MapFormat::Json),
+            io_map.to_bytes(MapFormat::Json)
+        );
+    }
+    #[test]
+    fn upgrades_v1_binary_files() {
+        let io_map = level();
+        let path = std::env::temp_dir().join(format!("game_attempt_v1_{}", std::process::id()));
+        std::fs::write(&path, v1_binary(&io_map)).unwrap();
+        let upgraded = upgrade_file(&path);
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(upgraded, Ok(Some(0)));
+        assert_eq!(binary_version(&data), Ok(BINARY_VERSION));
+        assert_eq!(data, io_map.to_bytes(MapFormat::Binary).unwrap());
+    }
+    #[test]
+    fn migrates_unversioned_json() {
+        let mut level = serde_json::to_value(level()).unwrap();
+        level.as_object_mut().unwrap().remove("version");
+        for screen in screens(&mut level).unwrap() {
+            screen.as_object_mut().unwrap().remove("platforms");
+        }
+        assert_eq!(version_of(&level), Ok(0));
+        migrate(&mut level).unwrap();
+        assert_eq!(version_of(&level), Ok(SCHEMA_VERSION));
+        for screen in screens(&mut level).unwrap() {
+            assert_eq!(screen["platforms"], json!([]));
+        }
+        serde_json::from_value::<IOMap>(level).unwrap();
+    }
+    #[test]
+    fn refuses_newer_levels() {
+        let mut level = serde_json::to_value(level()).unwrap();
+        level["version"] = json!(SCHEMA_VERSION + 1);
+        assert!(migrate(&mut level).is_err());
+    }
+}
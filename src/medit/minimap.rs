@@ -0,0 +1,131 @@
+use std::mem;
+
+use crate::{
+    consts::{
+        DARK_GREY, GREEN, LIGHT_GREY, MAGNETA, MEDIT_MINIMAP, MEDIT_TILE_SIZE, UI_LAYER, YELLOW,
+    },
+    internals::{
+        levels::{GridSpace, LevelGrid},
+        object::Behavior,
+    },
+    render::{rect::Rect, RenderJobID, RenderJobs},
+};
+
+// A map of every screen in the level, drawn in the editor's extra room. The current screen is
+// lighter, the screen the player starts on has a green square in it, and edges the player can
+// leave through are marked: transitions in yellow and wraps in magenta.
+pub struct Minimap {
+    jobs: Vec<RenderJobID>,
+    shown: Vec<([f32; 4], [f64; 4])>, // what's drawn right now: the color and bounds of each rectangle
+}
+impl Minimap {
+    pub fn new() -> Minimap {
+        Minimap {
+            jobs: Vec::new(),
+            shown: Vec::new(),
+        }
+    }
+    // Brings the minimap up to date. It's only redrawn if something changed.
+    pub fn update(
+        &mut self,
+        jobs: &mut RenderJobs,
+        grid: &[Vec<LevelGrid>],
+        current: [usize; 2],
+        start: [usize; 2],
+    ) {
+        let rects = rects(grid, current, start);
+        if rects == self.shown {
+            return;
+        }
+        for id in mem::take(&mut self.jobs) {
+            jobs.remove_job(id);
+        }
+        for (color, bounds) in &rects {
+            self.jobs
+                .push(jobs.add_job(Rect::new(*color, *bounds), UI_LAYER));
+        }
+        self.shown = rects;
+    }
+}
+impl Default for Minimap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the screen ([x, y]) under a position in pixels, if it's on the minimap
+pub fn screen_at(pos: [f64; 2], size: [usize; 2]) -> Option<[usize; 2]> {
+    let (corner, cell) = layout(size);
+    let x = (pos[0] - corner[0]) / cell;
+    let y = (pos[1] - corner[1]) / cell;
+    let inside = x >= 0.0 && y >= 0.0 && x < size[0] as f64 && y < size[1] as f64;
+    inside.then_some([x as usize, y as usize])
+}
+
+// the top left corner of the minimap and how big each screen is on it, in pixels. The screens
+// are kept square, and as big as they can be while still fitting.
+fn layout(size: [usize; 2]) -> ([f64; 2], f64) {
+    let [x, y, width, height] = MEDIT_MINIMAP.map(|x| x as f64 * MEDIT_TILE_SIZE);
+    let cell = (width / size[0].max(1) as f64).min(height / size[1].max(1) as f64);
+    ([x, y], cell)
+}
+
+fn rects(
+    grid: &[Vec<LevelGrid>],
+    current: [usize; 2],
+    start: [usize; 2],
+) -> Vec<([f32; 4], [f64; 4])> {
+    let size = [grid.first().map_or(0, Vec::len), grid.len()];
+    let (corner, cell) = layout(size);
+    let gap = (cell / 10.0).min(2.0); // between screens
+    let bar = cell / 6.0; // how thick the edge markings are
+    let mut res = Vec::new();
+    for (y, line) in grid.iter().enumerate() {
+        for (x, screen) in line.iter().enumerate() {
+            let [left, top] = [corner[0] + x as f64 * cell, corner[1] + y as f64 * cell];
+            let [left, top, side] = [left + gap / 2.0, top + gap / 2.0, cell - gap];
+            let color = if [x, y] == current {
+                LIGHT_GREY
+            } else {
+                DARK_GREY
+            };
+            res.push((color, [left, top, side, side]));
+            if [x, y] == start {
+                res.push((
+                    GREEN,
+                    [left + side / 3.0, top + side / 3.0, side / 3.0, side / 3.0],
+                ));
+            }
+            let edges = [
+                [left, top, side, bar],              // top
+                [left, top + side - bar, side, bar], // bottom
+                [left, top, bar, side],              // left
+                [left + side - bar, top, bar, side], // right
+            ];
+            for (edge, bounds) in edge_behaviors(screen).into_iter().zip(edges) {
+                match edge {
+                    Some(Behavior::Portal) => res.push((YELLOW, bounds)),
+                    Some(Behavior::Wrap) => res.push((MAGNETA, bounds)),
+                    _ => {}
+                }
+            }
+        }
+    }
+    res
+}
+
+// whether each edge of a screen (top, bottom, left, right) has a transition or wrap on it
+fn edge_behaviors(screen: &LevelGrid) -> [Option<Behavior>; 4] {
+    let contents = &screen.contents;
+    let last = contents.len().saturating_sub(1);
+    let row = |i: usize| contents.get(i).cloned().unwrap_or_default();
+    let column = |i: usize| contents.iter().filter_map(|x| x.get(i).copied()).collect();
+    let edges: [Vec<GridSpace>; 4] = [row(0), row(last), column(0), column(last)];
+    edges.map(|tiles| {
+        let behaviors: Vec<Behavior> = tiles.iter().map(|x| x.def().behavior).collect();
+        // a transition is the more important one to show
+        [Behavior::Portal, Behavior::Wrap]
+            .into_iter()
+            .find(|x| behaviors.contains(x))
+    })
+}
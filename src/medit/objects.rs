@@ -0,0 +1,106 @@
+use crate::{
+    consts::{LAYERS, MEDIT_OBJECT_SNAP},
+    internals::{
+        levels::GridSpace,
+        object::{Behavior, BlockTemplate, Direction},
+        tiles::TileKind,
+    },
+};
+
+use super::history::Snapshot;
+
+// Free-form objects: the BlockTemplates in LevelGrid::others. Unlike tiles they can go anywhere
+// and be any size. Positions and sizes are in tiles, from the top left of the screen.
+
+// an object that's being dragged around
+pub struct Drag {
+    pub grab: [f64; 2],   // where the object was grabbed, from its top left corner
+    pub before: Snapshot, // the level from before, for undoing
+    pub changed: bool,    // whether the object was placed or moved yet
+}
+
+// every behavior, in the order they're switched through in the editor
+const BEHAVIORS: [Behavior; 14] = [
+    Behavior::None,
+    Behavior::Stop,
+    Behavior::Kill,
+    Behavior::Move(Direction::Up),
+    Behavior::Move(Direction::Down),
+    Behavior::Move(Direction::Left),
+    Behavior::Move(Direction::Right),
+    Behavior::Advance,
+    Behavior::Wrap,
+    Behavior::Portal,
+    Behavior::Stick,
+    Behavior::Water,
+    Behavior::Slime,
+    Behavior::Flip,
+];
+
+// an object that looks and acts like a tile, placed at pos. Tiles that aren't blocks can't be objects.
+pub fn from_tile(tile: GridSpace, pos: [f64; 2]) -> Option<BlockTemplate> {
+    let def = tile.def();
+    if def.kind != TileKind::Block {
+        return None;
+    }
+    Some(BlockTemplate {
+        object: def.template().x_pos(pos[0]).y_pos(pos[1]),
+        behavior: def.behavior,
+    })
+}
+// [x, y, width, height], if the object has all of them
+pub fn bounds(block: &BlockTemplate) -> Option<[f64; 4]> {
+    let object = &block.object;
+    Some([object.x_pos?, object.y_pos?, object.width?, object.height?])
+}
+// the last (top-most) object that covers a position
+pub fn at(others: &[BlockTemplate], pos: [f64; 2]) -> Option<usize> {
+    others.iter().rposition(|block| {
+        bounds(block).is_some_and(|[x, y, width, height]| {
+            (x..x + width).contains(&pos[0]) && (y..y + height).contains(&pos[1])
+        })
+    })
+}
+// rounds a position or size to the nearest step objects can be placed at
+pub fn snap(val: f64) -> f64 {
+    (val / MEDIT_OBJECT_SNAP).round() * MEDIT_OBJECT_SNAP
+}
+// grows (or shrinks, with negative steps) an object by a number of snapping steps ([width, height])
+pub fn resize(block: &mut BlockTemplate, steps: [f64; 2]) {
+    let object = &mut block.object;
+    for (size, step) in [&mut object.width, &mut object.height]
+        .into_iter()
+        .zip(steps)
+    {
+        let new = size.unwrap_or(1.0) + step * MEDIT_OBJECT_SNAP;
+        *size = Some(new.max(MEDIT_OBJECT_SNAP));
+    }
+}
+// moves an object one layer forwards or backwards, staying within the layers there are
+pub fn change_layer(block: &mut BlockTemplate, forwards: bool) {
+    let layer = block.object.layer.unwrap_or(0);
+    block.object.layer = Some(match forwards {
+        true => (layer + 1).min(LAYERS - 1),
+        false => layer.saturating_sub(1),
+    });
+}
+// switches an object to the next behavior
+pub fn next_behavior(block: &mut BlockTemplate) {
+    let i = BEHAVIORS.iter().position(|x| *x == block.behavior);
+    block.behavior = BEHAVIORS[i.map_or(0, |x| (x + 1) % BEHAVIORS.len())];
+}
+// a short description of an object, for printing
+pub fn describe(block: &BlockTemplate) -> String {
+    let Some([x, y, width, height]) = bounds(block) else {
+        return "Object without a position or size".to_string();
+    };
+    format!(
+        "Object at [{}, {}], {} by {}, layer {}, behavior {:?}",
+        x,
+        y,
+        width,
+        height,
+        block.object.layer.unwrap_or(0),
+        block.behavior
+    )
+}
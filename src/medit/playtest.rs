@@ -0,0 +1,55 @@
+use std::mem;
+
+use piston::Key;
+
+use crate::{
+    input::InputVars,
+    internals::{
+        levels::{Level, Levels},
+        object::CollideAction,
+        timestep::Timestep,
+        Game,
+    },
+    render::RenderJobs,
+};
+
+// A game running on the level that's being edited. The editor's render jobs are put aside while it
+// runs, and handed back once it's over.
+pub struct Playtest {
+    game: Game,
+    timestep: Timestep,
+    editor_jobs: RenderJobs,
+}
+impl Playtest {
+    pub fn new(level: Level, jobs: &mut RenderJobs) -> Playtest {
+        Playtest {
+            game: Game::new(Levels::from_level(level)),
+            timestep: Timestep::new(),
+            editor_jobs: mem::replace(jobs, RenderJobs::new()),
+        }
+    }
+    // Runs the game for a frame. Returns true once the playtest is over: the player died, reached
+    // a goal, or pressed t to go back to editing.
+    pub fn tick(&mut self, jobs: &mut RenderJobs, input: &mut InputVars) -> bool {
+        if input.key_pressed(Key::T as u32) {
+            return true;
+        }
+        for _ in 0..self.timestep.ticks() {
+            let report = self.game.tick(input);
+            input.reset();
+            let over = report
+                .actions
+                .iter()
+                .any(|x| matches!(x, CollideAction::Kill | CollideAction::Advance));
+            if over {
+                return true;
+            }
+        }
+        self.game.render(jobs, self.timestep.alpha());
+        false
+    }
+    // throws away the game's render jobs, putting the editor's back
+    pub fn end(self, jobs: &mut RenderJobs) {
+        *jobs = self.editor_jobs;
+    }
+}
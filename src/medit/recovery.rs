@@ -0,0 +1,91 @@
+use std::{
+    ffi::OsString,
+    fs, panic,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use super::{IOMap, MapFormat};
+
+// Unsaved work is written next to the level as [level path].recovery every so often, and whenever
+// the editor panics. It's removed again once the level is saved.
+
+// The latest unsaved version of the level being edited, and the path of the level. It's kept here,
+// rather than in the editor, so that the panic hook can get to it.
+static PENDING: Mutex<Option<(PathBuf, IOMap)>> = Mutex::new(None);
+
+pub fn recovery_path(path: &Path) -> PathBuf {
+    let mut res = OsString::from(path);
+    res.push(".recovery");
+    PathBuf::from(res)
+}
+// keeps a version of the level to be written out by the next flush
+pub fn stash(path: &Path, io_map: IOMap) {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some((path.to_path_buf(), io_map));
+    }
+}
+// Writes out whatever's been stashed since the last flush. Returns whether there was anything.
+pub fn flush() -> Result<bool, String> {
+    // the lock is only tried, in case the panic happened while it was held
+    let Ok(mut pending) = PENDING.try_lock() else {
+        return Err("The level is being used elsewhere".to_string());
+    };
+    match pending.take() {
+        Some((path, io_map)) => io_map
+            .save(recovery_path(&path), MapFormat::Binary)
+            .map(|_| true),
+        None => Ok(false),
+    }
+}
+// Throws away the level's unsaved work, both stashed and written out. Called once it's been saved.
+pub fn discard(path: &Path) {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = None;
+    }
+    // there usually isn't a recovery file to remove
+    let _ = fs::remove_file(recovery_path(path));
+}
+// Makes panicking write out the unsaved work first.
+pub fn install_hook() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match flush() {
+            Ok(true) => eprintln!("Unsaved work was written to a recovery file"),
+            Ok(false) => {}
+            Err(e) => eprintln!("Writing the recovery file failed: {}", e),
+        }
+        default(info);
+    }));
+}
+// The recovery file of a level, if there's one that's newer than the level itself.
+pub fn load_newer(path: &Path) -> Option<IOMap> {
+    let recovery = recovery_path(path);
+    let modified = |x: &Path| fs::metadata(x).and_then(|x| x.modified()).ok();
+    let newer = match (modified(&recovery)?, modified(path)) {
+        (recovery, Some(level)) => recovery > level,
+        // the level was never saved at all
+        (_, None) => true,
+    };
+    if !newer {
+        return None;
+    }
+    IOMap::load(recovery).ok().map(|x| x.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_work_on_levels_that_were_never_saved() {
+        let path =
+            std::env::temp_dir().join(format!("game_attempt_never_saved_{}", std::process::id()));
+        assert!(load_newer(&path).is_none());
+        stash(&path, IOMap::new());
+        assert_eq!(flush(), Ok(true));
+        assert!(load_newer(&path).is_some());
+        discard(&path);
+        assert!(load_newer(&path).is_none());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    consts::{CONTENT_LAYER, DEFAULT_FONT_ID, GRID_SIZE, WHITE},
+    internals::object::{BlockTemplate, ObjectTemplate},
+    render::{text::TextRenderer, RenderJobComponent},
+};
+
+use super::{history::Snapshot, objects};
+
+// a text block that's being typed into
+pub struct Typing {
+    pub index: usize,     // which of the current screen's others it is
+    pub before: Snapshot, // the level from before typing started, for undoing
+}
+
+// how wide a character is, in tiles. The default font is monospaced, at one tile per line.
+const CHAR_WIDTH: f64 = 0.5;
+
+// Text blocks (signs) are BlockTemplates in LevelGrid::others that are drawn with a TextRenderer.
+// Positions are in tiles, from the top left of the screen.
+pub fn new_block(pos: [f64; 2]) -> BlockTemplate {
+    let mut job = TextRenderer::new(
+        String::new(),
+        [0.0; 4],
+        WHITE,
+        GRID_SIZE as u32,
+        -1,
+        -1,
+        DEFAULT_FONT_ID,
+    );
+    TextRenderer::ensure_mut(&mut job).multiline = true;
+    let mut res = BlockTemplate {
+        object: ObjectTemplate::new()
+            .x_pos(pos[0])
+            .y_pos(pos[1])
+            .job(job)
+            .layer(CONTENT_LAYER),
+        ..BlockTemplate::new()
+    };
+    fit(&mut res);
+    res
+}
+// the text renderer of a block, if it's a text block
+pub fn text_mut(block: &mut BlockTemplate) -> Option<&mut TextRenderer> {
+    match &mut block.object.job.as_mut()?.cmp {
+        RenderJobComponent::Text(val) => Some(val),
+        _ => None,
+    }
+}
+pub fn text(block: &BlockTemplate) -> Option<&str> {
+    match &block.object.job.as_ref()?.cmp {
+        RenderJobComponent::Text(val) => Some(&val.text),
+        _ => None,
+    }
+}
+// resizes a text block to fit around its text (at least a tile in each direction)
+pub fn fit(block: &mut BlockTemplate) {
+    let Some(text) = text(block) else {
+        return;
+    };
+    let columns = text.split('\n').map(|x| x.chars().count()).max();
+    let width = (columns.unwrap_or(0) as f64 * CHAR_WIDTH).max(1.0);
+    let height = text.split('\n').count() as f64;
+    block.object.width = Some(width);
+    block.object.height = Some(height);
+}
+// the text block under a position, if the top-most object there is one
+pub fn at(others: &[BlockTemplate], pos: [f64; 2]) -> Option<usize> {
+    objects::at(others, pos).filter(|x| text(&others[*x]).is_some())
+}
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+
+use crate::internals::levels::GridSpace;
+
+// How the editor paints tiles. Positions are all [row, column] within the current screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+    Pencil,  // one tile at a time, under the cursor
+    Rect,    // a filled rectangle, dragged from corner to corner
+    Outline, // just the edges of a rectangle
+    Line,    // a straight line, dragged from end to end
+    Fill,    // every connected tile that's the same as the one clicked on
+    Select,  // picks a rectangle to copy or cut, without painting anything
+    Text,    // places a text block and starts typing into it, or edits the one clicked on
+    Object,  // places, drags and edits free-form objects instead of tiles
+}
+impl Tool {
+    // whether the tool is used by dragging from one tile to another
+    pub fn drags(self) -> bool {
+        matches!(self, Tool::Rect | Tool::Outline | Tool::Line | Tool::Select)
+    }
+    // the tiles the tool covers when dragged from start to end (or used at end)
+    pub fn shape(
+        self,
+        start: [usize; 2],
+        end: [usize; 2],
+        contents: &[Vec<GridSpace>],
+    ) -> Vec<[usize; 2]> {
+        match self {
+            Tool::Pencil => vec![end],
+            Tool::Rect | Tool::Select => rect(start, end, true),
+            Tool::Outline => rect(start, end, false),
+            Tool::Line => line(start, end),
+            Tool::Fill => flood_fill(end, contents),
+            Tool::Text | Tool::Object => Vec::new(),
+        }
+    }
+}
+
+pub fn rect(start: [usize; 2], end: [usize; 2], filled: bool) -> Vec<[usize; 2]> {
+    let [top, bottom] = [start[0].min(end[0]), start[0].max(end[0])];
+    let [left, right] = [start[1].min(end[1]), start[1].max(end[1])];
+    let mut res = Vec::new();
+    for row in top..=bottom {
+        for column in left..=right {
+            let edge = row == top || row == bottom || column == left || column == right;
+            if filled || edge {
+                res.push([row, column]);
+            }
+        }
+    }
+    res
+}
+
+// Bresenham's line algorithm, so that the line is one tile thick with no gaps
+pub fn line(start: [usize; 2], end: [usize; 2]) -> Vec<[usize; 2]> {
+    let [mut row, mut column] = [start[0] as isize, start[1] as isize];
+    let [end_row, end_column] = [end[0] as isize, end[1] as isize];
+    let d_row = -(end_row - row).abs();
+    let d_column = (end_column - column).abs();
+    let step_row = if row < end_row { 1 } else { -1 };
+    let step_column = if column < end_column { 1 } else { -1 };
+    let mut error = d_column + d_row;
+    let mut res = Vec::new();
+    loop {
+        res.push([row as usize, column as usize]);
+        if row == end_row && column == end_column {
+            return res;
+        }
+        let doubled = 2 * error;
+        if doubled >= d_row {
+            error += d_row;
+            column += step_column;
+        }
+        if doubled <= d_column {
+            error += d_column;
+            row += step_row;
+        }
+    }
+}
+
+// every tile connected to start (not diagonally) that's the same as it
+pub fn flood_fill(start: [usize; 2], contents: &[Vec<GridSpace>]) -> Vec<[usize; 2]> {
+    let Some(target) = contents.get(start[0]).and_then(|x| x.get(start[1])) else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::from([start]);
+    let mut res = Vec::new();
+    let mut stack = vec![start];
+    while let Some([row, column]) = stack.pop() {
+        res.push([row, column]);
+        let next = [
+            [row.wrapping_sub(1), column],
+            [row + 1, column],
+            [row, column.wrapping_sub(1)],
+            [row, column + 1],
+        ];
+        for pos in next {
+            let same = contents.get(pos[0]).and_then(|x| x.get(pos[1])) == Some(target);
+            if same && seen.insert(pos) {
+                stack.push(pos);
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut tiles: Vec<[usize; 2]>) -> Vec<[usize; 2]> {
+        tiles.sort();
+        tiles
+    }
+    // whether each tile is next to the last one (diagonals count)
+    fn connected(tiles: &[[usize; 2]]) -> bool {
+        tiles.windows(2).all(|x| {
+            let [a, b] = [x[0], x[1]];
+            a != b && a[0].abs_diff(b[0]) <= 1 && a[1].abs_diff(b[1]) <= 1
+        })
+    }
+    #[test]
+    fn lines_go_from_end_to_end() {
+        for (start, end) in [
+            ([2, 3], [2, 9]),  // across
+            ([9, 3], [2, 3]),  // up
+            ([0, 0], [6, 6]),  // diagonal
+            ([6, 0], [0, 6]),  // the other diagonal
+            ([1, 4], [11, 6]), // steep
+            ([11, 6], [1, 4]), // steep, backwards
+            ([5, 5], [5, 5]),  // a single tile
+        ] {
+            let tiles = line(start, end);
+            assert_eq!(tiles.first(), Some(&start));
+            assert_eq!(tiles.last(), Some(&end));
+            assert!(connected(&tiles), "{tiles:?}");
+            // one tile thick: one tile per step along the longer side
+            let longest = start[0].abs_diff(end[0]).max(start[1].abs_diff(end[1]));
+            assert_eq!(tiles.len(), longest + 1);
+        }
+        assert_eq!(line([0, 0], [3, 3]), [[0, 0], [1, 1], [2, 2], [3, 3]]);
+    }
+    #[test]
+    fn fill_stops_at_other_tiles() {
+        let [empty, block] = [GridSpace(0), GridSpace(1)];
+        let mut contents = vec![vec![empty; 5]; 4];
+        // a wall down the middle, with the right side cut off from the left
+        for row in &mut contents {
+            row[2] = block;
+        }
+        let left = sorted(flood_fill([1, 0], &contents));
+        assert_eq!(left, rect([0, 0], [3, 1], true));
+        let wall = sorted(flood_fill([3, 2], &contents));
+        assert_eq!(wall, rect([0, 2], [3, 2], true));
+        // a gap in the wall lets it through, but not diagonally
+        contents[0][2] = empty;
+        contents[1][3] = block;
+        let through = flood_fill([3, 0], &contents);
+        assert_eq!(through.len(), 4 * 2 + 1 + 3 + 4);
+        assert!(!through.contains(&[1, 3]));
+    }
+    #[test]
+    fn fill_stops_at_the_edge() {
+        let contents = vec![vec![GridSpace(0); 3]; 3];
+        let corner = sorted(flood_fill([0, 0], &contents));
+        assert_eq!(corner, rect([0, 0], [2, 2], true));
+        assert!(flood_fill([3, 0], &contents).is_empty());
+        assert!(flood_fill([0, 3], &contents).is_empty());
+    }
+    #[test]
+    fn rects_can_be_dragged_from_any_corner() {
+        let filled = sorted(rect([1, 2], [3, 5], true));
+        assert_eq!(filled.len(), 3 * 4);
+        let outline = sorted(rect([1, 2], [3, 5], false));
+        // everything but the two tiles in the middle
+        assert_eq!(outline.len(), 3 * 4 - 2);
+        assert!(!outline.contains(&[2, 3]) && !outline.contains(&[2, 4]));
+        assert!(outline.iter().all(|x| filled.contains(x)));
+        for [start, end] in [[[3, 5], [1, 2]], [[1, 5], [3, 2]], [[3, 2], [1, 5]]] {
+            assert_eq!(sorted(rect(start, end, true)), filled);
+            assert_eq!(sorted(rect(start, end, false)), outline);
+        }
+        // too thin to have a middle
+        assert_eq!(rect([4, 1], [4, 3], false), rect([4, 1], [4, 3], true));
+    }
+}
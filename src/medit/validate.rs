@@ -0,0 +1,250 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    consts::TILES,
+    internals::{
+        levels::{GridSpace, LevelGrid},
+        object::Behavior,
+        tiles::TileKind,
+    },
+};
+
+use super::IOMap;
+
+const SIDE: usize = TILES + 2; // how many tiles across (and down) a screen is, including the hidden edges
+
+// everything that's wrong with a level. Errors break the level; warnings are probably mistakes.
+#[derive(Default)]
+pub struct Report {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// [screen x, screen y, row, column], just like IOMap::player_start
+type Spot = [usize; 4];
+
+// Checks a level for anything that would make it crash or impossible to beat. Endings (see
+// PackEntry::ending) are meant to be impossible to get out of, so they don't need a goal.
+pub fn validate(io_map: &IOMap, ending: bool) -> Report {
+    let mut report = Report::default();
+    check_structure(io_map, &mut report);
+    // the rest would have to index out of bounds to check a broken level
+    if !report.errors.is_empty() {
+        return report;
+    }
+    check_start(io_map, &mut report);
+    check_transitions(io_map, &mut report);
+    if report.errors.is_empty() && !ending && !goal_reachable(io_map) {
+        report
+            .errors
+            .push("No goal can be reached from the start".to_string());
+    }
+    report
+}
+
+fn check_structure(io_map: &IOMap, report: &mut Report) {
+    let [width, height] = io_map.size;
+    if io_map.grid.len() != height {
+        report.errors.push(format!(
+            "The level is {} screens tall, but its size says {}",
+            io_map.grid.len(),
+            height
+        ));
+    }
+    for (y, line) in io_map.grid.iter().enumerate() {
+        if line.len() != width {
+            report.errors.push(format!(
+                "Row {} of screens is {} screens wide, but the level's size says {}",
+                y,
+                line.len(),
+                width
+            ));
+        }
+        for (x, screen) in line.iter().enumerate() {
+            let square = screen.contents.len() == SIDE
+                && screen.contents.iter().all(|row| row.len() == SIDE);
+            if !square {
+                report.errors.push(format!(
+                    "Screen [{}, {}] isn't {} by {} tiles",
+                    x, y, SIDE, SIDE
+                ));
+            }
+        }
+    }
+}
+
+fn check_start(io_map: &IOMap, report: &mut Report) {
+    let start = io_map.player_start;
+    match tile(io_map, start) {
+        None => report.errors.push(format!(
+            "The player start {:?} is outside of the level",
+            start
+        )),
+        Some(tile) if tile != GridSpace::start() => report.errors.push(format!(
+            "The player start {:?} is a {}, not a {}",
+            start,
+            tile.def().name,
+            GridSpace::start().def().name
+        )),
+        _ => {}
+    }
+}
+
+// Transitions on the edge of a screen lead to the screen next to it. There should be a transition
+// on the other side to come back through.
+fn check_transitions(io_map: &IOMap, report: &mut Report) {
+    for (y, line) in io_map.grid.iter().enumerate() {
+        for (x, screen) in line.iter().enumerate() {
+            for (row, tiles) in screen.contents.iter().enumerate() {
+                for (column, space) in tiles.iter().enumerate() {
+                    if space.def().behavior != Behavior::Portal {
+                        continue;
+                    }
+                    let spot = [x, y, row, column];
+                    let edges = edges(row, column);
+                    if edges.is_empty() {
+                        // the game handles it, but there's nothing on the other side to check
+                        report.warnings.push(format!(
+                            "The transition at {:?} isn't on the edge of its screen",
+                            spot
+                        ));
+                    }
+                    for dir in edges {
+                        let other = across(io_map, spot, dir);
+                        let matching = tile(io_map, other)
+                            .is_some_and(|x| x.def().behavior == Behavior::Portal);
+                        if !matching {
+                            report.warnings.push(format!(
+                                "The transition at {:?} leads to {:?}, which isn't a transition",
+                                spot, other
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Whether the player can get from the start to a goal, going through transitions and wraps.
+// Jumping, gravity and moving blocks aren't taken into account, only which tiles are open.
+fn goal_reachable(io_map: &IOMap) -> bool {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([io_map.player_start]);
+    while let Some(spot) = queue.pop_front() {
+        if !seen.insert(spot) {
+            continue;
+        }
+        for dir in [[-1, 0], [1, 0], [0, -1], [0, 1]] {
+            let Some(next) = step(spot, dir) else {
+                continue;
+            };
+            let next = match tile(io_map, next).map(|x| x.def().behavior) {
+                Some(Behavior::Advance) => return true,
+                // going into a transition or wrap puts the player on the other side of a screen
+                Some(Behavior::Portal) => match step(across(io_map, next, dir), dir) {
+                    Some(val) => val,
+                    None => continue,
+                },
+                Some(Behavior::Wrap) => opposite_side(next, dir),
+                _ => next,
+            };
+            if tile(io_map, next).is_some_and(open) {
+                queue.push_back(next);
+            }
+        }
+    }
+    false
+}
+
+// whether the player can be in a tile
+fn open(tile: GridSpace) -> bool {
+    let def = tile.def();
+    match def.kind {
+        TileKind::Empty | TileKind::Player => true,
+        TileKind::Enemy => false,
+        TileKind::Block => matches!(
+            def.behavior,
+            Behavior::None | Behavior::Water | Behavior::Slime | Behavior::Flip
+        ),
+    }
+}
+
+fn tile(io_map: &IOMap, spot: Spot) -> Option<GridSpace> {
+    let [x, y, row, column] = spot;
+    let screen: &LevelGrid = io_map.grid.get(y)?.get(x)?;
+    screen.contents.get(row)?.get(column).copied()
+}
+
+// the tile next to this one in a direction ([row, column]), if it's on the same screen
+fn step(spot: Spot, dir: [isize; 2]) -> Option<Spot> {
+    let row = spot[2].checked_add_signed(dir[0]).filter(|x| *x < SIDE)?;
+    let column = spot[3].checked_add_signed(dir[1]).filter(|x| *x < SIDE)?;
+    Some([spot[0], spot[1], row, column])
+}
+
+// the directions ([row, column]) that lead off the screen from an edge tile
+fn edges(row: usize, column: usize) -> Vec<[isize; 2]> {
+    let mut res = Vec::new();
+    if row == 0 {
+        res.push([-1, 0]);
+    }
+    if row == SIDE - 1 {
+        res.push([1, 0]);
+    }
+    if column == 0 {
+        res.push([0, -1]);
+    }
+    if column == SIDE - 1 {
+        res.push([0, 1]);
+    }
+    res
+}
+
+// where the player ends up on the same screen after wrapping: just inside the opposite edge
+fn opposite_side(spot: Spot, dir: [isize; 2]) -> Spot {
+    let [x, y, mut row, mut column] = spot;
+    match dir {
+        [-1, 0] => row = SIDE - 2,
+        [1, 0] => row = 1,
+        [0, -1] => column = SIDE - 2,
+        _ => column = 1,
+    }
+    [x, y, row, column]
+}
+
+// the matching tile on the next screen over (wrapping around the level), on its opposite edge
+fn across(io_map: &IOMap, spot: Spot, dir: [isize; 2]) -> Spot {
+    let [width, height] = io_map.size;
+    let x = (spot[0] as isize + dir[1]).rem_euclid(width as isize) as usize;
+    let y = (spot[1] as isize + dir[0]).rem_euclid(height as isize) as usize;
+    let edge = |pos: usize, dir: isize| match dir {
+        -1 => SIDE - 1,
+        1 => 0,
+        _ => pos,
+    };
+    [x, y, edge(spot[2], dir[0]), edge(spot[3], dir[1])]
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internals::tiles::tiles;
+
+    #[test]
+    fn endings_dont_need_a_goal() {
+        let (io_map, _) = IOMap::load("assets/levels/l3").unwrap();
+        assert!(!validate(&io_map, false).errors.is_empty());
+        assert!(validate(&io_map, true).errors.is_empty());
+    }
+    #[test]
+    fn warns_about_transitions_inside_screens() {
+        let (mut io_map, _) = IOMap::load("assets/levels/l1").unwrap();
+        let portal = tiles().named("Transition").unwrap();
+        io_map.grid[0][0].contents[20][20] = portal;
+        let report = validate(&io_map, false);
+        assert!(report
+            .warnings
+            .iter()
+            .any(|x| x.contains("[0, 0, 20, 20] isn't on the edge")));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{
+    consts::{MEDIT_MAX_ZOOM, MEDIT_TILES, MEDIT_TILE_SIZE, MEDIT_ZOOM_STEP},
+    internals::object::Transform,
+};
+
+// How the grid is shown: zoomed in by some amount and panned around. Only the grid (and what's on
+// it) is affected; the guide, minimap and status bar always stay where they are.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct View {
+    pub zoom: f64,        // 1 shows the whole screen
+    pub offset: [f64; 2], // where the top left corner of the screen is drawn, in pixels
+}
+impl View {
+    pub fn new() -> View {
+        View {
+            zoom: 1.0,
+            offset: [0.0; 2],
+        }
+    }
+    // how big a tile is drawn, in pixels
+    pub fn tile_size(&self) -> f64 {
+        MEDIT_TILE_SIZE * self.zoom
+    }
+    // where a position in pixels is on the screen, in tiles (not rounded)
+    pub fn to_tiles(&self, pos: [f64; 2]) -> [f64; 2] {
+        [
+            (pos[0] - self.offset[0]) / self.tile_size(),
+            (pos[1] - self.offset[1]) / self.tile_size(),
+        ]
+    }
+    // where something ([x, y, width, height] in tiles) is drawn, in pixels
+    pub fn to_pixels(&self, bounds: [f64; 4]) -> [f64; 4] {
+        let size = self.tile_size();
+        [
+            bounds[0] * size + self.offset[0],
+            bounds[1] * size + self.offset[1],
+            bounds[2] * size,
+            bounds[3] * size,
+        ]
+    }
+    // for drawing objects through the view
+    pub fn transform(&self) -> Transform {
+        let size = self.tile_size();
+        Transform {
+            tile_offset: [self.offset[0] / size, self.offset[1] / size],
+            tile_size: [size; 2],
+        }
+    }
+    // Zooms in (or out, with negative steps) by a number of scroll wheel steps, keeping whatever's
+    // under pos (in pixels) in place.
+    pub fn zoom_at(&mut self, pos: [f64; 2], steps: f64) {
+        let anchor = self.to_tiles(pos);
+        self.zoom = (self.zoom * MEDIT_ZOOM_STEP.powf(steps)).clamp(1.0, MEDIT_MAX_ZOOM);
+        let size = self.tile_size();
+        self.offset = [pos[0] - anchor[0] * size, pos[1] - anchor[1] * size];
+        self.clamp();
+    }
+    // moves the screen by some amount of pixels
+    pub fn pan(&mut self, by: [f64; 2]) {
+        self.offset = [self.offset[0] + by[0], self.offset[1] + by[1]];
+        self.clamp();
+    }
+    // keeps the screen covering the whole grid area, so there's never empty room around it
+    fn clamp(&mut self) {
+        let room = MEDIT_TILES as f64 * MEDIT_TILE_SIZE;
+        let drawn = MEDIT_TILES as f64 * self.tile_size();
+        self.offset = self.offset.map(|x| x.clamp(room - drawn, 0.0));
+    }
+}
+impl Default for View {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOM: f64 = MEDIT_TILES as f64 * MEDIT_TILE_SIZE;
+
+    fn close(a: [f64; 2], b: [f64; 2]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9)
+    }
+    // the area the grid covers, in pixels
+    fn covers(view: &View) -> bool {
+        let [x, y, width, height] =
+            view.to_pixels([0.0, 0.0, MEDIT_TILES as f64, MEDIT_TILES as f64]);
+        x <= 1e-9 && y <= 1e-9 && x + width >= ROOM - 1e-9 && y + height >= ROOM - 1e-9
+    }
+
+    #[test]
+    fn pixels_and_tiles_round_trip() {
+        for zoom in [1.0, 1.5, 2.0, MEDIT_MAX_ZOOM] {
+            for offset in [[0.0, 0.0], [-37.5, -120.0], [-250.0, -3.0]] {
+                let view = View { zoom, offset };
+                for tile in [[0.0, 0.0], [3.25, 7.5], [MEDIT_TILES as f64 - 0.5, 1.0]] {
+                    let [x, y, _, _] = view.to_pixels([tile[0], tile[1], 1.0, 1.0]);
+                    assert!(close(view.to_tiles([x, y]), tile), "{view:?} {tile:?}");
+                }
+            }
+        }
+    }
+    #[test]
+    fn zooming_keeps_the_anchor_in_place() {
+        let mut view = View::new();
+        for (pos, steps) in [
+            ([100.0, 200.0], 2.0),
+            ([310.0, 45.0], 1.0),
+            ([200.0, 200.0], -1.0),
+        ] {
+            let before = view.to_tiles(pos);
+            view.zoom_at(pos, steps);
+            assert!(close(view.to_tiles(pos), before), "{view:?} {pos:?}");
+        }
+    }
+    #[test]
+    fn stays_inside_the_level() {
+        // all the way out, there's nowhere to pan
+        let mut view = View::new();
+        view.pan([500.0, -500.0]);
+        assert_eq!(view, View::new());
+        view.zoom_at([0.0, 0.0], -5.0);
+        assert_eq!(view, View::new());
+        // all the way in, it stops at every edge
+        view.zoom_at([ROOM / 2.0, ROOM / 2.0], 1000.0);
+        assert_eq!(view.zoom, MEDIT_MAX_ZOOM);
+        assert!(covers(&view));
+        for by in [[1e6, 1e6], [-1e6, -1e6], [1e6, -1e6], [-1e6, 1e6]] {
+            view.pan(by);
+            assert!(covers(&view), "{view:?}");
+        }
+        view.pan([1e6, 1e6]);
+        assert_eq!(view.offset, [0.0, 0.0]);
+        view.pan([-1e6, -1e6]);
+        assert_eq!(
+            view.offset,
+            [ROOM - MEDIT_TILES as f64 * view.tile_size(); 2]
+        );
+        // zooming out from a corner can't leave empty room either
+        view.zoom_at([ROOM, ROOM], -1.0);
+        assert!(covers(&view));
+    }
+}
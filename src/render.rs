@@ -1,4 +1,8 @@
+pub mod animation;
+pub mod assets;
+pub mod camera;
 pub mod composite;
+pub mod raster;
 pub mod rect;
 pub mod text;
 pub mod texture;
@@ -6,7 +10,7 @@ pub mod toggle;
 
 use std::{collections::BTreeMap, thread::sleep, time::Instant};
 
-use graphics::{color::BLACK, Context, Graphics};
+use graphics::{color::BLACK, CharacterCache, Context, Graphics, Transformed};
 use opengl_graphics::{GlGraphics, GlyphCache};
 use piston::{
     Button, ButtonState, Event, EventSettings, Events, Input, Loop, Motion, RenderArgs, UpdateArgs,
@@ -16,12 +20,14 @@ use piston_window::PistonWindow;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    consts::{FRAMERATE, LAYERS, LAYER_SIZE, OPENGL, TITLE, TRANSPARENT},
+    consts::{FRAMERATE, LAYERS, LAYER_SIZE, OPENGL, TITLE, TRANSPARENT, UI_LAYER},
     input::InputVars,
     render::text::TextRenderer,
 };
 
 use self::{
+    animation::Animation,
+    camera::Camera,
     composite::Composite,
     rect::Rect,
     texture::{ImageRenderer, TextureBuffer},
@@ -60,18 +66,15 @@ impl<'a> Window<'a> {
         }
     }
     pub fn render(&mut self, args: &RenderArgs) {
-        let job_list: Vec<&mut RenderJob> = self.jobs.all_jobs().collect();
-        let gl = &mut self.gl;
+        let jobs = &mut self.jobs;
         let fonts = &mut self.fonts;
         let textures = &self.textures;
-        gl.draw(args.viewport(), |c, g| {
-            g.clear_color(BLACK);
-            for job in job_list {
-                job.render(&c, g, fonts, textures)
-            }
-        });
+        self.gl
+            .draw(args.viewport(), |c, g| jobs.draw(&c, g, fonts, textures));
+    }
+    pub fn update(&mut self, args: &UpdateArgs) {
+        self.jobs.advance(args.dt);
     }
-    pub fn update(&mut self, _args: &UpdateArgs) {}
     pub fn handle_input(&mut self, input: Input) -> bool {
         match input {
             Input::Button(val) => match val.button {
@@ -88,19 +91,17 @@ impl<'a> Window<'a> {
                 Button::Controller(_) => todo!(),
                 Button::Hat(_) => todo!(),
             },
-            Input::Move(val) => {
-                match val {
-                    Motion::MouseCursor(pos) => {
-                        self.input.mouse_pos = pos;
-                    }
-                    Motion::MouseScroll(_) => {
-                        // TODO: Insert scroll behavior when needed
-                    }
-                    _ => {}
+            Input::Move(val) => match val {
+                Motion::MouseCursor(pos) => {
+                    self.input.update_mouse_pos(pos);
                 }
-            }
+                Motion::MouseScroll(delta) => {
+                    self.input.update_scroll(delta);
+                }
+                _ => {}
+            },
             Input::Text(text) => {
-                self.input.text_buffer.push_str(&text);
+                self.input.push_text(&text);
             }
             Input::Resize(val) => {
                 self.input.update_dimension(val.window_size);
@@ -117,8 +118,8 @@ impl<'a> Window<'a> {
         true
     }
     // runs a single frame. Returns false if the loop should end.
+    // The input isn't reset here: whoever consumes it calls InputVars::reset once they're done with it.
     pub fn run_loop_iteration(&mut self) -> bool {
-        self.input.reset();
         let now = Instant::now();
         let to_sleep = FRAMERATE.saturating_sub(now - self.last_time);
         sleep(to_sleep);
@@ -145,15 +146,54 @@ impl<'a> Window<'a> {
 // A structure that handles items to render.
 pub struct RenderJobs {
     internal: BTreeMap<RenderJobID, RenderJob>,
-    count: Vec<u64>, // one count is kept per layer
+    count: Vec<u64>,            // one count is kept per layer
+    finished: Vec<RenderJobID>, // jobs with one-shot animations that finished since they were last checked
+    pub camera: Camera,
 }
 impl RenderJobs {
     pub fn new() -> RenderJobs {
         RenderJobs {
             internal: BTreeMap::new(),
             count: vec![0; LAYERS as usize],
+            finished: Vec::new(),
+            camera: Camera::new(),
+        }
+    }
+    // Draws every job onto a cleared frame, with whichever backend: the window's, or a Raster.
+    pub fn draw<G, C>(
+        &mut self,
+        c: &Context,
+        g: &mut G,
+        fonts: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
+        g.clear_color(BLACK);
+        let camera = self.camera.pos;
+        let world = c.trans(-camera[0], -camera[1]);
+        for (id, job) in &mut self.internal {
+            // only the world moves with the camera, not the UI
+            let context = match RenderJobs::get_layer(*id) < UI_LAYER {
+                true => &world,
+                false => c,
+            };
+            job.render(context, g, fonts, textures)
+        }
+    }
+    // moves every animation along by dt seconds
+    pub fn advance(&mut self, dt: f64) {
+        for (id, job) in &mut self.internal {
+            if job.cmp.advance(dt) {
+                self.finished.push(*id);
+            }
         }
     }
+    // the jobs whose one-shot animations have finished since this was last called
+    pub fn finished_animations(&mut self) -> Vec<RenderJobID> {
+        std::mem::take(&mut self.finished)
+    }
     pub fn add_job(&mut self, job: RenderJob, layer: u64) -> RenderJobID {
         let res = RenderJobID(self.count[layer as usize] + layer * LAYER_SIZE);
         self.internal.insert(res, job);
@@ -190,21 +230,75 @@ pub enum RenderJobComponent {
     Image(ImageRenderer),
     Composite(Composite),
     Toggle(Toggle),
+    Animation(Animation),
 }
 impl RenderJobComponent {
-    pub fn render(
+    pub fn render<G, C>(
         &mut self,
         context: &Context,
-        graphics: &mut GlGraphics,
-        font: &mut Vec<GlyphCache>,
-        textures: &TextureBuffer,
-    ) {
+        graphics: &mut G,
+        font: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
         match self {
             RenderJobComponent::Rect(val) => val.render(context, graphics),
             RenderJobComponent::Text(val) => val.render(context, graphics, font),
             RenderJobComponent::Image(val) => val.render(context, graphics, textures),
             RenderJobComponent::Composite(val) => val.render(context, graphics, font, textures),
             RenderJobComponent::Toggle(val) => val.render(context, graphics, font, textures),
+            RenderJobComponent::Animation(val) => val.render(context, graphics, font, textures),
+        }
+    }
+    // Renders the job as a part of a bigger one: its bounds are relative to the bigger one's (0 to
+    // 1 across), and its tint is multiplied by the bigger one's. It's changed and then put back,
+    // rather than copied.
+    pub fn render_within<G, C>(
+        &mut self,
+        bounds: [f64; 4],
+        tint: [f32; 4],
+        context: &Context,
+        graphics: &mut G,
+        font: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
+        let (old_bounds, old_tint) = (*self.bounds(), *self.tint());
+        let own = self.bounds();
+        *own = [
+            bounds[0] + own[0] * bounds[2],
+            bounds[1] + own[1] * bounds[3],
+            own[2] * bounds[2],
+            own[3] * bounds[3],
+        ];
+        for (own, tint) in self.tint().iter_mut().zip(tint) {
+            *own *= tint;
+        }
+        self.render(context, graphics, font, textures);
+        *self.bounds() = old_bounds;
+        *self.tint() = old_tint;
+    }
+    // Moves any animations in the job along by dt seconds. Returns true if a one-shot animation
+    // finished just now.
+    pub fn advance(&mut self, dt: f64) -> bool {
+        match self {
+            RenderJobComponent::Animation(val) => val.advance(dt),
+            RenderJobComponent::Composite(val) => val.advance(dt),
+            RenderJobComponent::Toggle(val) => val.advance(dt),
+            _ => false,
+        }
+    }
+    // whether there's an animation anywhere in the job, so that advancing it changes it
+    pub fn animated(&self) -> bool {
+        match self {
+            RenderJobComponent::Animation(_) => true,
+            RenderJobComponent::Composite(val) => val.animated(),
+            RenderJobComponent::Toggle(val) => val.jobs.iter().any(RenderJobComponent::animated),
+            _ => false,
         }
     }
     pub fn bounds(&mut self) -> &mut [f64; 4] {
@@ -214,6 +308,7 @@ impl RenderJobComponent {
             RenderJobComponent::Image(val) => &mut val.bounds,
             RenderJobComponent::Composite(val) => val.bounds(),
             RenderJobComponent::Toggle(val) => &mut val.bounds,
+            RenderJobComponent::Animation(val) => &mut val.bounds,
         }
     }
     pub fn tint(&mut self) -> &mut [f32; 4] {
@@ -223,9 +318,11 @@ impl RenderJobComponent {
             RenderJobComponent::Image(val) => &mut val.tint,
             RenderJobComponent::Composite(val) => val.tint(),
             RenderJobComponent::Toggle(val) => &mut val.tint,
+            RenderJobComponent::Animation(val) => &mut val.tint,
         }
     }
 }
+// A component plus whether it's drawn. Each component's `new` hands back one of these, ready to add.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RenderJob {
     pub cmp: RenderJobComponent,
@@ -235,13 +332,16 @@ impl RenderJob {
     pub fn default() -> RenderJob {
         Rect::new(TRANSPARENT, [0.0; 4])
     }
-    fn render(
+    fn render<G, C>(
         &mut self,
         context: &Context,
-        graphics: &mut GlGraphics,
-        font: &mut Vec<GlyphCache>,
-        textures: &TextureBuffer,
-    ) {
+        graphics: &mut G,
+        font: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
         if self.enabled {
             self.cmp.render(context, graphics, font, textures);
         }
@@ -0,0 +1,130 @@
+use graphics::{CharacterCache, Context, Graphics};
+use serde::{Deserialize, Serialize};
+
+use super::{texture::TextureBuffer, RenderJob, RenderJobComponent};
+
+// what an animation does once it gets to its last frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum AnimationMode {
+    #[default]
+    Loop, // starts over from the first frame
+    PingPong, // plays backwards to the first frame, then forwards again
+    Once,     // stays on the last frame, and is finished. Decorations in a level are removed then.
+}
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Frame {
+    pub job: RenderJobComponent, // bounds are relative to the animation's, like a Toggle's
+    pub duration: f64,           // how long the frame is shown for, in seconds
+}
+// Shows a series of frames, each for a set amount of time. Time is moved along by
+// RenderJobs::advance, so the speed doesn't depend on the frame rate.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    #[serde(default)]
+    pub mode: AnimationMode,
+    pub bounds: [f64; 4],
+    pub tint: [f32; 4],
+    #[serde(default)]
+    pub time: f64, // how long it's been playing for, in seconds
+    #[serde(default)]
+    pub finished: bool, // whether a one-shot animation got to its end
+}
+impl Animation {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        frames: Vec<Frame>,
+        mode: AnimationMode,
+        bounds: [f64; 4],
+        tint: [f32; 4],
+    ) -> RenderJob {
+        RenderJob {
+            enabled: true,
+            cmp: RenderJobComponent::Animation(Animation {
+                frames,
+                mode,
+                bounds,
+                tint,
+                time: 0.0,
+                finished: false,
+            }),
+        }
+    }
+    // Moves the animation along by dt seconds. Returns true if it finished just now.
+    pub fn advance(&mut self, dt: f64) -> bool {
+        self.time += dt;
+        if self.mode == AnimationMode::Once && !self.finished && self.time >= self.length() {
+            self.finished = true;
+            return true;
+        }
+        false
+    }
+    // plays the animation again from the start
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+        self.finished = false;
+    }
+    // the frames in the order they're played, before it starts over
+    fn order(&self) -> Vec<usize> {
+        let count = self.frames.len();
+        let mut res: Vec<usize> = (0..count).collect();
+        if self.mode == AnimationMode::PingPong {
+            // the first and last frames aren't shown twice in a row
+            res.extend((1..count.saturating_sub(1)).rev());
+        }
+        res
+    }
+    // how long it takes to play through once, in seconds
+    fn length(&self) -> f64 {
+        self.order().iter().map(|x| self.frames[*x].duration).sum()
+    }
+    // the frame that's showing right now
+    pub fn current(&self) -> usize {
+        let length = self.length();
+        if length <= 0.0 {
+            return 0;
+        }
+        let mut time = match self.mode {
+            AnimationMode::Once if self.time >= length => return self.frames.len() - 1,
+            AnimationMode::Once => self.time,
+            AnimationMode::Loop | AnimationMode::PingPong => self.time % length,
+        };
+        for i in self.order() {
+            time -= self.frames[i].duration;
+            if time < 0.0 {
+                return i;
+            }
+        }
+        self.frames.len() - 1
+    }
+    pub fn render<G, C>(
+        &mut self,
+        context: &Context,
+        graphics: &mut G,
+        font: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
+        if self.frames.is_empty() {
+            return;
+        }
+        let current = self.current();
+        self.frames[current].job.render_within(
+            self.bounds,
+            self.tint,
+            context,
+            graphics,
+            font,
+            textures,
+        );
+    }
+    // Attempts to convert a renderjob into an animation. Panics if it fails.
+    pub fn ensure_mut(orig: &mut RenderJob) -> &mut Animation {
+        match &mut orig.cmp {
+            RenderJobComponent::Animation(res) => res,
+            _ => panic!("Ensure failed!"),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{ASSETS_FOLDER, ASSET_MANIFEST_PATH};
+
+use super::texture::TextureID;
+
+static MANIFEST: OnceLock<Result<AssetManifest, String>> = OnceLock::new();
+
+// The asset manifest everything uses. It's read from the assets folder the first time it's needed.
+pub fn manifest() -> Result<&'static AssetManifest, String> {
+    MANIFEST
+        .get_or_init(|| AssetManifest::load(&asset_path(ASSET_MANIFEST_PATH)))
+        .as_ref()
+        .map_err(String::clone)
+}
+
+// Turns a path relative to the assets folder into one that can be opened. Paths in asset files
+// always use '/' between folders, whatever the platform.
+pub fn asset_path(relative: &str) -> PathBuf {
+    relative
+        .split('/')
+        .fold(PathBuf::from(ASSETS_FOLDER), |path, part| path.join(part))
+}
+
+// Every texture and font there is, by name. They're loaded in the order they're listed in, which
+// is the order their IDs go in.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AssetManifest {
+    pub textures: Vec<AssetEntry>,
+    pub fonts: Vec<AssetEntry>, // the first one is the default font
+}
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AssetEntry {
+    pub name: String,
+    pub path: String, // relative to the assets folder (see asset_path)
+}
+impl AssetManifest {
+    pub fn load(path: &Path) -> Result<AssetManifest, String> {
+        let data = fs::read(path).map_err(|x| format!("{}: {}", path.display(), x))?;
+        serde_json::from_slice(&data).map_err(|x| format!("{}: {}", path.display(), x))
+    }
+    // the ID a texture will have once it's loaded
+    pub fn texture_id(&self, name: &str) -> Option<TextureID> {
+        self.textures
+            .iter()
+            .position(|x| x.name == name)
+            .map(TextureID)
+    }
+    // the name of a texture, by its ID
+    pub fn texture_name(&self, id: TextureID) -> Option<&str> {
+        self.textures.get(id.0).map(|x| x.name.as_str())
+    }
+    // reads every font file, in order
+    pub fn read_fonts(&self) -> Result<Vec<Vec<u8>>, String> {
+        self.fonts
+            .iter()
+            .map(|font| {
+                let path = asset_path(&font.path);
+                fs::read(&path).map_err(|x| format!("{}: {}", path.display(), x))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+use crate::consts::{CAMERA_DEADZONE, WINDOW_X, WINDOW_Y};
+
+// What part of the world is on screen. Jobs on layers below UI_LAYER are in world pixels and get
+// moved by the camera when they're drawn; UI_LAYER and up are in screen pixels and stay put.
+pub struct Camera {
+    pub pos: [f64; 2],  // the top left corner of what's shown, in world pixels
+    pub view: [f64; 2], // how much of the world is shown, in pixels
+    // How far the target can get from the middle of the view (as a fraction of the view) before the
+    // camera starts following it.
+    pub deadzone: [f64; 2],
+    pub bounds: Option<[f64; 4]>, // the world ([x, y, width, height]), which the camera doesn't go outside of
+}
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            pos: [0.0; 2],
+            view: [WINDOW_X as f64, WINDOW_Y as f64],
+            deadzone: CAMERA_DEADZONE,
+            bounds: None,
+        }
+    }
+    // Moves the camera just far enough to keep a target ([x, y, width, height]) inside the deadzone.
+    pub fn follow(&mut self, target: [f64; 4]) {
+        for i in 0..2 {
+            let middle = target[i] + target[i + 2] / 2.0;
+            let center = self.pos[i] + self.view[i] / 2.0;
+            let reach = self.view[i] * self.deadzone[i];
+            if middle < center - reach {
+                self.pos[i] = middle + reach - self.view[i] / 2.0;
+            } else if middle > center + reach {
+                self.pos[i] = middle - reach - self.view[i] / 2.0;
+            }
+        }
+        self.clamp();
+    }
+    // keeps the view inside the world. A world smaller than the view is kept at the top left.
+    pub fn clamp(&mut self) {
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+        for i in 0..2 {
+            let max = bounds[i] + bounds[i + 2] - self.view[i];
+            self.pos[i] = self.pos[i].min(max).max(bounds[i]);
+        }
+    }
+}
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,4 @@
-use graphics::Context;
-use opengl_graphics::{GlGraphics, GlyphCache};
+use graphics::{CharacterCache, Context, Graphics};
 use serde::{Deserialize, Serialize};
 
 use super::{texture::TextureBuffer, RenderJob, RenderJobComponent};
@@ -27,20 +26,21 @@ impl Composite {
             .for_each(std::mem::drop);
         &mut self.tint
     }
-    // Attempts to convert a renderjob into a composite. Panics if it fails. 
+    // Attempts to convert a renderjob into a composite. Panics if it fails.
     pub fn ensure_mut(orig: &mut RenderJob) -> &mut Composite {
         match &mut orig.cmp {
             RenderJobComponent::Composite(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
-    // Attempts to convert a renderjob into a composite. Panics if it fails. 
+    // Attempts to convert a renderjob into a composite. Panics if it fails.
     pub fn ensure(orig: &RenderJob) -> &Composite {
         match &orig.cmp {
             RenderJobComponent::Composite(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(bounds: [f64; 4], tint: [f32; 4]) -> RenderJob {
         RenderJob {
             enabled: true,
@@ -72,23 +72,48 @@ impl Composite {
         tint[3] *= self.tint[3];
         self.cache[index] = Some(job2);
     }
+    // Moves the animations of the enabled jobs along. Returns true if a one-shot animation finished just now.
+    pub fn advance(&mut self, dt: f64) -> bool {
+        let mut res = false;
+        for i in 0..self.jobs.len() {
+            // the cached copies of animations are out of date once they've moved along
+            if self.enabled[i] && self.jobs[i].animated() {
+                res |= self.jobs[i].advance(dt);
+                self.cache[i] = None;
+            }
+        }
+        res
+    }
+    pub fn animated(&self) -> bool {
+        self.jobs.iter().any(RenderJobComponent::animated)
+    }
     pub fn toggle_job(&mut self, index: usize, state: bool) {
+        // animations start over whenever they're switched on
+        if let (true, false, RenderJobComponent::Animation(val)) =
+            (state, self.enabled[index], &mut self.jobs[index])
+        {
+            val.restart();
+            self.cache[index] = None;
+        }
         self.enabled[index] = state;
     }
     pub fn get_job(&mut self, index: usize) -> &mut RenderJobComponent {
         self.cache[index] = None;
         &mut self.jobs[index]
     }
-    pub fn render(
+    pub fn render<G, C>(
         &mut self,
         context: &Context,
-        graphics: &mut GlGraphics,
-        font: &mut Vec<GlyphCache>,
-        textures: &TextureBuffer,
-    ) {
+        graphics: &mut G,
+        font: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
         for i in 0..self.cache.len() {
             if self.enabled[i] {
-                if let None = self.cache[i] {
+                if self.cache[i].is_none() {
                     self.update_cache(i);
                 }
                 let job = self.cache[i].as_mut().unwrap();
@@ -97,3 +122,37 @@ impl Composite {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        consts::WHITE,
+        render::{
+            animation::{Animation, AnimationMode, Frame},
+            rect::Rect,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn only_animations_are_redone() {
+        let mut job = Composite::new([0.0, 0.0, 10.0, 10.0], WHITE);
+        let composite = Composite::ensure_mut(&mut job);
+        composite.add_job(Rect::new(WHITE, [0.0, 0.0, 1.0, 1.0]), true);
+        let frame = Frame {
+            job: Rect::new(WHITE, [0.0, 0.0, 1.0, 1.0]).cmp,
+            duration: 0.5,
+        };
+        let animation = Animation::new(vec![frame], AnimationMode::Once, [0.0; 4], WHITE);
+        composite.add_job(animation, true);
+        composite.update_cache(0);
+        composite.update_cache(1);
+        assert!(!composite.advance(0.25));
+        assert!(composite.cache[0].is_some());
+        assert!(composite.cache[1].is_none());
+        // and the one-shot animation finishes once
+        assert!(composite.advance(0.5));
+        assert!(!composite.advance(0.5));
+    }
+}
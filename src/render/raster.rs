@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use graphics::{
+    draw_state::Blend, glyph_cache::rusttype::GlyphCache, types::Color, CharacterCache, Context,
+    DrawState, Graphics, ImageSize, Viewport,
+};
+use image::RgbaImage;
+use opengl_graphics::{CreateTexture, Format, TextureOp, TextureSettings, UpdateTexture};
+
+use super::{
+    texture::{LoadTexture, TextureBuffer},
+    RenderJobs,
+};
+
+// how finely triangle corners are placed within a pixel, so that edges shared by two triangles
+// are worked out exactly the same way for both
+const SUBPIXELS: i64 = 256;
+
+// fonts for drawing onto a Raster
+pub type RasterGlyphCache<'a> = GlyphCache<'a, (), RasterTexture>;
+
+// A texture kept in memory, for drawing onto a Raster.
+pub struct RasterTexture(pub RgbaImage);
+impl ImageSize for RasterTexture {
+    fn get_size(&self) -> (u32, u32) {
+        self.0.dimensions()
+    }
+}
+impl LoadTexture for RasterTexture {
+    fn from_path(path: &Path) -> Result<RasterTexture, String> {
+        let image = image::open(path).map_err(|x| x.to_string())?;
+        Ok(RasterTexture(image.to_rgba8()))
+    }
+}
+// glyph caches make their textures through these
+impl TextureOp<()> for RasterTexture {
+    type Error = String;
+}
+impl CreateTexture<()> for RasterTexture {
+    fn create<S: Into<[u32; 2]>>(
+        factory: &mut (),
+        format: Format,
+        memory: &[u8],
+        size: S,
+        _settings: &TextureSettings,
+    ) -> Result<Self, String> {
+        let [width, height] = size.into();
+        let mut res = RasterTexture(RgbaImage::new(width, height));
+        res.update(factory, format, memory, [0, 0], [width, height])?;
+        Ok(res)
+    }
+}
+impl UpdateTexture<()> for RasterTexture {
+    // Rgba8 is the only format there is
+    fn update<O, S>(
+        &mut self,
+        _factory: &mut (),
+        _format: Format,
+        memory: &[u8],
+        offset: O,
+        size: S,
+    ) -> Result<(), String>
+    where
+        O: Into<[u32; 2]>,
+        S: Into<[u32; 2]>,
+    {
+        let ([x0, y0], [width, height]) = (offset.into(), size.into());
+        if x0 + width > self.0.width() || y0 + height > self.0.height() {
+            return Err("The update doesn't fit in the texture".to_string());
+        }
+        if memory.len() < (width * height * 4) as usize {
+            return Err("There isn't enough memory for the update".to_string());
+        }
+        for (i, pixel) in memory
+            .chunks_exact(4)
+            .take((width * height) as usize)
+            .enumerate()
+        {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            self.0
+                .get_pixel_mut(x0 + x, y0 + y)
+                .0
+                .copy_from_slice(pixel);
+        }
+        Ok(())
+    }
+}
+// A frame drawn in memory instead of on the GPU, so it can be drawn without a window (or a graphics
+// card) and then saved, or compared against a reference image.
+pub struct Raster {
+    pub image: RgbaImage,
+}
+impl Raster {
+    pub fn new(width: u32, height: u32) -> Raster {
+        Raster {
+            image: RgbaImage::new(width, height),
+        }
+    }
+    // the context a window of the same size would draw with
+    pub fn context(&self) -> Context {
+        let (width, height) = self.image.dimensions();
+        Context::new_viewport(Viewport {
+            rect: [0, 0, width as i32, height as i32],
+            draw_size: [width, height],
+            window_size: [width as f64, height as f64],
+        })
+    }
+    // draws every job, the same way the window would
+    pub fn draw<C>(
+        &mut self,
+        jobs: &mut RenderJobs,
+        fonts: &mut Vec<C>,
+        textures: &TextureBuffer<RasterTexture>,
+    ) where
+        C: CharacterCache<Texture = RasterTexture>,
+    {
+        let context = self.context();
+        jobs.draw(&context, self, fonts, textures);
+    }
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.image
+            .save(path)
+            .map_err(|x| format!("{}: {}", path.display(), x))
+    }
+    // Checks the frame against a reference image. Each channel can be off by up to tolerance, so
+    // that small rounding differences don't count.
+    pub fn compare(&self, reference: &Path, tolerance: u8) -> Result<(), String> {
+        let expected = image::open(reference)
+            .map_err(|x| format!("{}: {}", reference.display(), x))?
+            .to_rgba8();
+        if expected.dimensions() != self.image.dimensions() {
+            return Err(format!(
+                "The frame is {:?}, but {} is {:?}",
+                self.image.dimensions(),
+                reference.display(),
+                expected.dimensions()
+            ));
+        }
+        let mut first = None;
+        let mut count = 0;
+        for ((x, y, a), b) in self.image.enumerate_pixels().zip(expected.pixels()) {
+            if a.0.iter().zip(b.0).any(|(a, b)| a.abs_diff(b) > tolerance) {
+                first.get_or_insert([x, y]);
+                count += 1;
+            }
+        }
+        match first {
+            None => Ok(()),
+            Some([x, y]) => Err(format!(
+                "{} pixels differ from {}, the first at ({}, {})",
+                count,
+                reference.display(),
+                x,
+                y
+            )),
+        }
+    }
+    // where a vertex (from -1 to 1 across, and upwards) is in the frame, in subpixels
+    fn to_subpixels(&self, vertex: [f32; 2]) -> [i64; 2] {
+        let (width, height) = self.image.dimensions();
+        let x = (vertex[0] as f64 + 1.0) / 2.0 * width as f64;
+        let y = (1.0 - vertex[1] as f64) / 2.0 * height as f64;
+        [x, y].map(|x| (x * SUBPIXELS as f64).round() as i64)
+    }
+    // Fills in a triangle. shade gets how close a pixel is to each corner (adding up to 1), and
+    // gives its color.
+    fn triangle(
+        &mut self,
+        blend: Option<Blend>,
+        corners: [[f32; 2]; 3],
+        mut shade: impl FnMut([f64; 3]) -> Color,
+    ) {
+        let mut points = corners.map(|x| self.to_subpixels(x));
+        let mut order = [0, 1, 2];
+        let mut area = edge(points[0], points[1], points[2]);
+        if area < 0 {
+            points.swap(1, 2);
+            order.swap(1, 2);
+            area = -area;
+        }
+        if area == 0 {
+            return;
+        }
+        let (width, height) = self.image.dimensions();
+        let range = |i: usize, size: u32| {
+            let min = points.iter().map(|x| x[i]).min().unwrap_or(0);
+            let max = points.iter().map(|x| x[i]).max().unwrap_or(0);
+            let min = min.div_euclid(SUBPIXELS).max(0);
+            let max = max.div_euclid(SUBPIXELS).min(size as i64 - 1);
+            min..=max
+        };
+        let xs = range(0, width);
+        for y in range(1, height) {
+            for x in xs.clone() {
+                let center = [x * SUBPIXELS + SUBPIXELS / 2, y * SUBPIXELS + SUBPIXELS / 2];
+                let mut weights = [0.0; 3];
+                let mut inside = true;
+                for i in 0..3 {
+                    let (a, b) = (points[(i + 1) % 3], points[(i + 2) % 3]);
+                    let value = edge(a, b, center);
+                    // pixels right on an edge only belong to the triangle if it's a top or left
+                    // edge, so they aren't drawn twice where two triangles meet
+                    inside &= value > 0 || (value == 0 && top_left(a, b));
+                    weights[order[i]] = value as f64 / area as f64;
+                }
+                if inside {
+                    let color = shade(weights);
+                    self.blend(x as u32, y as u32, color, blend);
+                }
+            }
+        }
+    }
+    fn blend(&mut self, x: u32, y: u32, color: Color, blend: Option<Blend>) {
+        let pixel = self.image.get_pixel_mut(x, y);
+        let below = pixel.0.map(|x| x as f32 / 255.0);
+        let res = match blend {
+            None => color,
+            // the game only draws with ordinary transparency, so every blend mode is treated like it
+            Some(_) => {
+                let alpha = color[3];
+                let mut res = [0.0; 4];
+                for i in 0..3 {
+                    res[i] = color[i] * alpha + below[i] * (1.0 - alpha);
+                }
+                res[3] = alpha + below[3] * (1.0 - alpha);
+                res
+            }
+        };
+        pixel.0 = res.map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8);
+    }
+}
+impl Graphics for Raster {
+    type Texture = RasterTexture;
+    fn clear_color(&mut self, color: Color) {
+        let color = color.map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8);
+        for pixel in self.image.pixels_mut() {
+            pixel.0 = color;
+        }
+    }
+    // nothing's drawn with stencils
+    fn clear_stencil(&mut self, _value: u8) {}
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        f(&mut |vertices| {
+            for tri in vertices.chunks_exact(3) {
+                self.triangle(draw_state.blend, [tri[0], tri[1], tri[2]], |_| *color);
+            }
+        });
+    }
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |vertices, colors| {
+            for (tri, colors) in vertices.chunks_exact(3).zip(colors.chunks_exact(3)) {
+                self.triangle(draw_state.blend, [tri[0], tri[1], tri[2]], |weights| {
+                    mix(weights, colors)
+                });
+            }
+        });
+    }
+    fn tri_list_uv<F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &RasterTexture,
+        mut f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        f(&mut |vertices, uvs| {
+            for (tri, uvs) in vertices.chunks_exact(3).zip(uvs.chunks_exact(3)) {
+                self.triangle(draw_state.blend, [tri[0], tri[1], tri[2]], |weights| {
+                    let texel = sample(texture, weights, uvs);
+                    [0, 1, 2, 3].map(|i| texel[i] * color[i])
+                });
+            }
+        });
+    }
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, texture: &RasterTexture, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |vertices, uvs, colors| {
+            for ((tri, uvs), colors) in vertices
+                .chunks_exact(3)
+                .zip(uvs.chunks_exact(3))
+                .zip(colors.chunks_exact(3))
+            {
+                self.triangle(draw_state.blend, [tri[0], tri[1], tri[2]], |weights| {
+                    let texel = sample(texture, weights, uvs);
+                    let color = mix(weights, colors);
+                    [0, 1, 2, 3].map(|i| texel[i] * color[i])
+                });
+            }
+        });
+    }
+}
+// Which side of the line from a to b that p is on: positive on the inside of a triangle whose
+// corners go clockwise (on the screen), and 0 right on the line.
+fn edge(a: [i64; 2], b: [i64; 2], p: [i64; 2]) -> i64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+// whether the edge from a to b (going clockwise) is along the top or the left of its triangle
+fn top_left(a: [i64; 2], b: [i64; 2]) -> bool {
+    (a[1] == b[1] && b[0] > a[0]) || b[1] < a[1]
+}
+// blends the colors at a triangle's corners
+fn mix(weights: [f64; 3], colors: &[[f32; 4]]) -> Color {
+    [0, 1, 2, 3].map(|i| (0..3).map(|j| colors[j][i] * weights[j] as f32).sum())
+}
+// the texture's color at a point in a triangle, using the nearest texel
+fn sample(texture: &RasterTexture, weights: [f64; 3], uvs: &[[f32; 2]]) -> Color {
+    let (width, height) = texture.0.dimensions();
+    let uv = [0, 1].map(|i| (0..3).map(|j| uvs[j][i] as f64 * weights[j]).sum::<f64>());
+    let x = ((uv[0] * width as f64) as u32).min(width.saturating_sub(1));
+    let y = ((uv[1] * height as f64) as u32).min(height.saturating_sub(1));
+    texture.0.get_pixel(x, y).0.map(|x| x as f32 / 255.0)
+}
@@ -1,5 +1,4 @@
-use graphics::{Context, Rectangle};
-use opengl_graphics::GlGraphics;
+use graphics::{Context, Graphics, Rectangle};
 use serde::{Deserialize, Serialize};
 
 use super::{RenderJob, RenderJobComponent};
@@ -9,7 +8,7 @@ pub struct Rect {
     pub bounds: [f64; 4],
 }
 impl Rect {
-    pub fn render(&self, context: &Context, graphics: &mut GlGraphics) {
+    pub fn render<G: Graphics>(&self, context: &Context, graphics: &mut G) {
         Rectangle::new(self.color).draw(
             self.bounds,
             &context.draw_state,
@@ -17,20 +16,21 @@ impl Rect {
             graphics,
         );
     }
+    #[allow(clippy::new_ret_no_self)]
     pub const fn new(color: [f32; 4], bounds: [f64; 4]) -> RenderJob {
         RenderJob {
             cmp: RenderJobComponent::Rect(Rect { color, bounds }),
             enabled: true,
         }
     }
-    // Attempts to convert a renderjob into a rectangle. Panics if it fails. 
+    // Attempts to convert a renderjob into a rectangle. Panics if it fails.
     pub fn ensure_mut(orig: &mut RenderJob) -> &mut Rect {
         match &mut orig.cmp {
             RenderJobComponent::Rect(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
-    // Attempts to convert a renderjob into a rectangle. Panics if it fails. 
+    // Attempts to convert a renderjob into a rectangle. Panics if it fails.
     pub fn ensure(orig: &RenderJob) -> &Rect {
         match &orig.cmp {
             RenderJobComponent::Rect(res) => res,
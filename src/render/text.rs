@@ -1,7 +1,6 @@
 use std::borrow::Cow;
 
-use graphics::{types::FontSize, CharacterCache, Context, Text, Transformed};
-use opengl_graphics::{GlGraphics, GlyphCache};
+use graphics::{types::FontSize, CharacterCache, Context, Graphics, Text, Transformed};
 use piston::Size;
 use serde::{Deserialize, Serialize};
 
@@ -18,8 +17,13 @@ pub struct TextRenderer {
     pub halign: i8, // -1 = left, 0 = center, 1 = right
     pub valign: i8, // -1 = top, 0 = center, 1 = bottom
     pub font: FontID,
+    // The editor's text (and signs placed with it) has bounds of [x, y, width, height], and each
+    // line is aligned on its own. Otherwise, the bounds are [x0, y0, x1, y1] and it's one line.
+    #[serde(default)]
+    pub multiline: bool,
 }
 impl TextRenderer {
+    #[allow(clippy::new_ret_no_self)]
     pub const fn new(
         text: String,
         bounds: [f64; 4],
@@ -38,6 +42,7 @@ impl TextRenderer {
                 halign,
                 valign,
                 font,
+                multiline: false,
             }),
             enabled: true,
         }
@@ -60,26 +65,35 @@ impl TextRenderer {
                 halign,
                 valign,
                 font,
+                multiline: false,
             }),
             enabled: true,
         }
     }
-    
-    // Attempts to convert a renderjob into a text object. Panics if it fails. 
+
+    // Attempts to convert a renderjob into a text object. Panics if it fails.
     pub fn ensure_mut(orig: &mut RenderJob) -> &mut TextRenderer {
         match &mut orig.cmp {
             RenderJobComponent::Text(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
-    // Attempts to convert a renderjob into a text object. Panics if it fails. 
+    // Attempts to convert a renderjob into a text object. Panics if it fails.
     pub fn ensure(orig: &RenderJob) -> &TextRenderer {
         match &orig.cmp {
             RenderJobComponent::Text(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
-    pub fn render(&self, context: &Context, graphics: &mut GlGraphics, font: &mut Vec<GlyphCache>) {
+    pub fn render<G, C>(&self, context: &Context, graphics: &mut G, font: &mut [C])
+    where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
+        if self.multiline {
+            self.render_lines(context, graphics, font);
+            return;
+        }
         let x0 = self.bounds[0];
         let y0 = self.bounds[1];
         let x1 = self.bounds[2];
@@ -111,10 +125,45 @@ impl TextRenderer {
             transform,
             graphics,
         )
-        .unwrap();
+        .unwrap_or_else(|_| panic!("Error drawing text!"));
+    }
+    // Draws the text inside its bounds ([x, y, width, height]). Each line is aligned on its own.
+    fn render_lines<G, C>(&self, context: &Context, graphics: &mut G, font: &mut [C])
+    where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
+        let [x0, y0, width, height] = self.bounds;
+        let t = Text::new_color(self.color, self.size);
+        let line_height = self.size as f64;
+        let lines: Vec<&str> = self.text.split('\n').collect();
+        let text_height = line_height * lines.len() as f64;
+        fn align(p0: f64, space: f64, wh: f64, align: i8) -> f64 {
+            match align {
+                -1 => p0,
+                1 => p0 + space - wh,
+                _ => p0 + (space / 2.0) - (wh / 2.0),
+            }
+        }
+        let top = align(y0, height, text_height, self.valign);
+        for (i, line) in lines.into_iter().enumerate() {
+            let size = measure(line, &mut font[self.font.0], self.size);
+            let x = align(x0, width, size.width, self.halign);
+            // text is drawn from its baseline, which sits most of the way down the line
+            let y = top + line_height * (i as f64 + 0.8);
+            let transform = context.transform.trans(x, y);
+            t.draw(
+                line,
+                &mut font[self.font.0],
+                &context.draw_state,
+                transform,
+                graphics,
+            )
+            .unwrap_or_else(|_| panic!("Error drawing text!"));
+        }
     }
 }
-fn measure(text: &str, cache: &mut GlyphCache, fs: FontSize) -> Size {
+fn measure<C: CharacterCache>(text: &str, cache: &mut C, fs: FontSize) -> Size {
     let mut w = 0.0;
     let mut h = 0.0;
     for ch in text.chars() {
@@ -124,7 +173,7 @@ fn measure(text: &str, cache: &mut GlyphCache, fs: FontSize) -> Size {
         h = (character.advance_height() + top).max(h);
     }
     Size {
-        width: w as f64,
-        height: h as f64,
+        width: w,
+        height: h,
     }
 }
@@ -1,28 +1,104 @@
-use graphics::{Context, Image};
-use opengl_graphics::{GlGraphics, Texture};
-use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
 
-use super::{RenderJob, RenderJobComponent};
+use graphics::{Context, Graphics, Image};
+use opengl_graphics::{Texture, TextureSettings};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-pub struct TextureBuffer {
-    pub textures: Vec<Texture>,
+use super::{
+    assets::{self, asset_path, AssetManifest},
+    RenderJob, RenderJobComponent,
+};
+
+// Textures for whichever backend is drawing: the window's (on the GPU) or a Raster's (in memory).
+pub struct TextureBuffer<T = Texture> {
+    pub textures: Vec<T>,
+    names: HashMap<String, TextureID>,
 }
-impl TextureBuffer {
-    pub fn new() -> TextureBuffer {
+impl<T> TextureBuffer<T> {
+    pub fn new() -> TextureBuffer<T> {
         TextureBuffer {
             textures: Vec::new(),
+            names: HashMap::new(),
         }
     }
-    pub fn add(&mut self, texture: Texture) -> TextureID {
+    pub fn add(&mut self, name: &str, texture: T) -> TextureID {
         self.textures.push(texture);
-        TextureID(self.textures.len() - 1)
+        let id = TextureID(self.textures.len() - 1);
+        self.names.insert(name.to_string(), id);
+        id
+    }
+    pub fn id(&self, name: &str) -> Option<TextureID> {
+        self.names.get(name).copied()
     }
-    pub fn get(&self, id: &TextureID) -> &Texture {
+    pub fn get(&self, id: &TextureID) -> &T {
         &self.textures[id.0]
     }
 }
-#[derive(Clone, Copy, Serialize, Deserialize)]
+impl<T: LoadTexture> TextureBuffer<T> {
+    pub fn load(&mut self, name: &str, path: &Path) -> Result<TextureID, String> {
+        let texture = T::from_path(path).map_err(|x| format!("{}: {}", path.display(), x))?;
+        Ok(self.add(name, texture))
+    }
+    // Loads every texture in the manifest, in order, so that they get the IDs the manifest gives them.
+    pub fn load_manifest(&mut self, manifest: &AssetManifest) -> Result<(), String> {
+        for entry in &manifest.textures {
+            if self.id(&entry.name).is_some() {
+                return Err(format!(
+                    "There's more than one texture named {}",
+                    entry.name
+                ));
+            }
+            self.load(&entry.name, &asset_path(&entry.path))?;
+        }
+        Ok(())
+    }
+}
+// textures that can be read from image files
+pub trait LoadTexture: Sized {
+    fn from_path(path: &Path) -> Result<Self, String>;
+}
+impl LoadTexture for Texture {
+    fn from_path(path: &Path) -> Result<Texture, String> {
+        Texture::from_path(path, &TextureSettings::new())
+    }
+}
+#[derive(Clone, Copy)]
 pub struct TextureID(pub usize);
+// Textures can be written as their number or, in readable files, as their name in the asset manifest.
+// Readable files get the name whenever the manifest has one.
+impl Serialize for TextureID {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = serializer
+            .is_human_readable()
+            .then(assets::manifest)
+            .and_then(Result::ok)
+            .and_then(|x| x.texture_name(*self));
+        match name {
+            Some(name) => serializer.serialize_str(name),
+            None => serializer.serialize_u64(self.0 as u64),
+        }
+    }
+}
+impl<'de> Deserialize<'de> for TextureID {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Id(usize),
+            Name(String),
+        }
+        if !deserializer.is_human_readable() {
+            return usize::deserialize(deserializer).map(TextureID);
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Id(id) => Ok(TextureID(id)),
+            Raw::Name(name) => assets::manifest()
+                .map_err(de::Error::custom)?
+                .texture_id(&name)
+                .ok_or_else(|| de::Error::custom(format!("There's no texture named {}", name))),
+        }
+    }
+}
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ImageRenderer {
     pub bounds: [f64; 4],
@@ -30,7 +106,12 @@ pub struct ImageRenderer {
     pub texture: TextureID,
 }
 impl ImageRenderer {
-    pub fn render(&self, context: &Context, graphics: &mut GlGraphics, textures: &TextureBuffer) {
+    pub fn render<G: Graphics>(
+        &self,
+        context: &Context,
+        graphics: &mut G,
+        textures: &TextureBuffer<G::Texture>,
+    ) {
         Image::new().color(self.tint).rect(self.bounds).draw(
             textures.get(&self.texture),
             &context.draw_state,
@@ -38,6 +119,7 @@ impl ImageRenderer {
             graphics,
         );
     }
+    #[allow(clippy::new_ret_no_self)]
     pub const fn new(bounds: [f64; 4], tint: [f32; 4], texture: TextureID) -> RenderJob {
         RenderJob {
             cmp: RenderJobComponent::Image(ImageRenderer {
@@ -48,15 +130,15 @@ impl ImageRenderer {
             enabled: true,
         }
     }
-    
-    // Attempts to convert a renderjob into a text object. Panics if it fails. 
+
+    // Attempts to convert a renderjob into a text object. Panics if it fails.
     pub fn ensure_mut(orig: &mut RenderJob) -> &mut ImageRenderer {
         match &mut orig.cmp {
             RenderJobComponent::Image(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
-    // Attempts to convert a renderjob into a text object. Panics if it fails. 
+    // Attempts to convert a renderjob into a text object. Panics if it fails.
     pub fn ensure(orig: &RenderJob) -> &ImageRenderer {
         match &orig.cmp {
             RenderJobComponent::Image(res) => res,
@@ -64,3 +146,30 @@ impl ImageRenderer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bincode::Options;
+
+    use super::*;
+
+    #[test]
+    fn readable_files_use_texture_names() {
+        let manifest = assets::manifest().unwrap();
+        let name = &manifest.textures[0].name;
+        let json = serde_json::to_string(&TextureID(0)).unwrap();
+        assert_eq!(json, format!("\"{}\"", name));
+        let read: TextureID = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.0, 0);
+        // textures the manifest doesn't have are still written, as their number
+        let missing = manifest.textures.len();
+        assert_eq!(
+            serde_json::to_string(&TextureID(missing)).unwrap(),
+            missing.to_string()
+        );
+        let options = bincode::DefaultOptions::new();
+        let binary = options.serialize(&TextureID(3)).unwrap();
+        assert_eq!(binary, options.serialize(&3usize).unwrap());
+        assert_eq!(options.deserialize::<TextureID>(&binary).unwrap().0, 3);
+    }
+}
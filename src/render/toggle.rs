@@ -1,5 +1,4 @@
-use graphics::Context;
-use opengl_graphics::{GlGraphics, GlyphCache};
+use graphics::{CharacterCache, Context, Graphics};
 use serde::{Deserialize, Serialize};
 
 use super::{texture::TextureBuffer, RenderJob, RenderJobComponent};
@@ -10,15 +9,14 @@ pub struct Toggle {
     pub enabled: usize, // which job is enabled
     pub bounds: [f64; 4],
     pub tint: [f32; 4],
-    pub anim: bool, // if true, enabled increases by 1 each frames
 }
 impl Toggle {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(
         possibilities: Vec<RenderJobComponent>,
         enabled: usize,
         bounds: [f64; 4],
         tint: [f32; 4],
-        animation: bool,
     ) -> RenderJob {
         RenderJob {
             enabled: true,
@@ -27,41 +25,40 @@ impl Toggle {
                 enabled,
                 bounds,
                 tint,
-                anim: animation,
             }),
         }
     }
-    pub fn render(
+    pub fn render<G, C>(
         &mut self,
         context: &Context,
-        graphics: &mut GlGraphics,
-        font: &mut Vec<GlyphCache>,
-        textures: &TextureBuffer,
-    ) {
-        let mut job2 = self.jobs[self.enabled].clone();
-        let bounds = job2.bounds();
-        bounds[0] = self.bounds[0] + bounds[0] * self.bounds[2];
-        bounds[1] = self.bounds[1] + bounds[1] * self.bounds[3];
-        bounds[2] *= self.bounds[2];
-        bounds[3] *= self.bounds[3];
-        let tint = job2.tint();
-        tint[0] *= self.tint[0];
-        tint[1] *= self.tint[1];
-        tint[2] *= self.tint[2];
-        tint[3] *= self.tint[3];
-        job2.render(context, graphics, font, textures);
-        if self.anim {
-            self.enabled = (self.enabled + 1) % self.jobs.len();
-        }
+        graphics: &mut G,
+        font: &mut Vec<C>,
+        textures: &TextureBuffer<G::Texture>,
+    ) where
+        G: Graphics,
+        C: CharacterCache<Texture = G::Texture>,
+    {
+        self.jobs[self.enabled].render_within(
+            self.bounds,
+            self.tint,
+            context,
+            graphics,
+            font,
+            textures,
+        );
+    }
+    // only the enabled job's animations move along
+    pub fn advance(&mut self, dt: f64) -> bool {
+        self.jobs[self.enabled].advance(dt)
     }
-    // Attempts to convert a renderjob into a toggle object. Panics if it fails. 
+    // Attempts to convert a renderjob into a toggle object. Panics if it fails.
     pub fn ensure_mut(orig: &mut RenderJob) -> &mut Toggle {
         match &mut orig.cmp {
             RenderJobComponent::Toggle(res) => res,
             _ => panic!("Ensure failed!"),
         }
     }
-    // Attempts to convert a renderjob into a toggle object. Panics if it fails. 
+    // Attempts to convert a renderjob into a toggle object. Panics if it fails.
     pub fn ensure(orig: &RenderJob) -> &Toggle {
         match &orig.cmp {
             RenderJobComponent::Toggle(res) => res,
@@ -0,0 +1,52 @@
+// Golden image tests: the game runs headlessly through the snapshot command, and its last frame is
+// checked against a reference image in tests/golden. After a change that's meant to change how the
+// game looks, run them with UPDATE_GOLDEN=1 to write new reference images (and look at them first).
+use std::{fs, path::Path, process::Command};
+
+fn check(name: &str, level: &str, replay: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let reference = root
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.png", name));
+    let out =
+        std::env::temp_dir().join(format!("game_attempt_{}_{}.png", name, std::process::id()));
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_game_attempt"));
+    command
+        .current_dir(root)
+        .args(["snapshot", level, replay])
+        .arg(&out);
+    if !update {
+        command.arg(&reference);
+    }
+    let output = command.output().expect("The game didn't run");
+    if update && output.status.success() {
+        fs::copy(&out, &reference).expect("The reference image couldn't be written");
+    }
+    let _ = fs::remove_file(&out);
+    assert!(
+        output.status.success(),
+        "{}: {}{}",
+        name,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn first_level() {
+    check("l1", "assets/levels/l1", "60");
+}
+#[test]
+fn replayed_level() {
+    check("l2", "assets/levels/l2", "tests/golden/replay.json");
+}
+#[test]
+fn level_pack() {
+    check(
+        "pack",
+        "assets/levels/pack.json",
+        "tests/golden/replay.json",
+    );
+}